@@ -6,28 +6,41 @@ pub mod shared;
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 pub mod split;
 pub mod stopwatch;
+#[doc(alias = "local_sync")]
 pub mod sync;
-mod time;
+pub mod time;
 
 pub mod prelude {
+    pub use crate::local_select;
     pub use crate::sealed;
     pub use crate::shared::*;
     #[cfg(feature = "tokio")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
     pub use crate::split as local_split;
-    pub use crate::stopwatch::Stopwatch;
+    pub use crate::stopwatch::{ResultStopwatch, Stopwatch, StopwatchRegistry, StopwatchWith};
     pub use crate::sync::bounded as local_bounded;
+    pub use crate::sync::bounded::channel as local_spsc_channel;
+    pub use crate::sync::broadcast as local_broadcast;
     pub use crate::sync::condvar as local_condvar;
     pub use crate::sync::error as local_sync_error;
+    pub use crate::sync::keyed as local_keyed;
+    pub use crate::sync::merge as local_merge;
+    pub use crate::sync::mutex as local_mutex;
+    pub use crate::sync::notify as local_notify;
     pub use crate::sync::oneshot as local_oneshot;
     #[cfg(feature = "tokio")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
     pub use crate::sync::pipe as local_pipe;
+    pub use crate::sync::rwlock as local_rwlock;
+    pub use crate::sync::select as local_race;
     pub use crate::sync::semaphore as local_semaphore;
     pub use crate::sync::unbounded as local_unbounded;
+    pub use crate::sync::unbounded::channel as local_mpsc_channel;
+    pub use crate::sync::watch as local_watch;
     pub use crate::{
-        debug_stopwatch, error_stopwatch, info_stopwatch, trace_stopwatch, warn_stopwatch,
+        aggregate_stopwatch, debug_stopwatch, error_stopwatch, error_stopwatch_on_err,
+        info_stopwatch, stopwatch, time_block, trace_stopwatch, warn_stopwatch,
     };
-    pub use crate::{define_with, define_with_unchecked};
+    pub use crate::{define_with, define_with_unchecked, project_field};
     pub use crate::{millisec, min, sec};
 }
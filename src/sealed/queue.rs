@@ -1,6 +1,6 @@
 use super::utils::UnsafeWrapper;
 use std::collections::{VecDeque, vec_deque};
-use std::fmt;
+use std::{fmt, mem};
 
 /// FIFO queue that never leaks references to its content
 pub struct Queue<T>(UnsafeWrapper<VecDeque<T>>);
@@ -24,6 +24,34 @@ impl<T> Queue<T> {
         unsafe { self.0.with(|inner| inner.pop_front()) }
     }
 
+    pub fn push_front(&self, item: T) {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.push_front(item)) }
+    }
+
+    pub fn pop_back(&self) -> Option<T> {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.pop_back()) }
+    }
+
+    /// Push all items from `iter` to the back of the queue.
+    /// The queue appears empty to any accesses made from within the iterator.
+    pub fn extend<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.with_detached(|content| content.extend(iter))
+    }
+
+    /// Invoke `f` on the front item without removing it.
+    /// The queue appears empty to any accesses made from within `f`.
+    pub fn front_map<R, F>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.with_detached(|content| content.front().map(f))
+    }
+
     pub fn contains(&self, item: &T) -> bool
     where
         T: PartialEq<T>,
@@ -32,6 +60,14 @@ impl<T> Queue<T> {
         unsafe { self.0.with(|inner| inner.contains(item)) }
     }
 
+    /// Check whether any item satisfies `pred`. The queue appears empty to any accesses made from within `pred`.
+    pub fn contains_by<F>(&self, pred: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_detached(|content| content.iter().any(pred))
+    }
+
     pub fn remove_all(&self, item: &T) -> bool
     where
         T: PartialEq<T>,
@@ -46,6 +82,68 @@ impl<T> Queue<T> {
         }
     }
 
+    /// Remove all items for which `pred` returns `true`. Returns `true` if anything was removed.
+    /// The queue appears empty to any accesses made from within `pred`.
+    pub fn remove_if<F>(&self, mut pred: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_detached(|content| {
+            let initial_len = content.len();
+            content.retain(|e| !pred(e));
+            content.len() != initial_len
+        })
+    }
+
+    /// Remove and return the first item for which `pred` returns `true`.
+    /// The queue appears empty to any accesses made from within `pred`.
+    pub fn remove_first<F>(&self, mut pred: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_detached(|content| {
+            let index = content.iter().position(&mut pred)?;
+            content.remove(index)
+        })
+    }
+
+    /// Remove and return the first item for which `pred` returns `true`, replacing it with the last item.
+    /// Faster than [`remove_first()`](Queue::remove_first) but doesn't preserve the order of the queue.
+    /// The queue appears empty to any accesses made from within `pred`.
+    pub fn swap_remove_first<F>(&self, mut pred: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_detached(|content| {
+            let index = content.iter().position(&mut pred)?;
+            content.swap_remove_back(index)
+        })
+    }
+
+    /// Move all items out of the queue in FIFO order, leaving it empty.
+    pub fn take_all(&self) -> VecDeque<T> {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(mem::take) }
+    }
+
+    /// Clone the current content of the queue in FIFO order.
+    /// The queue appears empty to any accesses made from within `T::clone()`.
+    pub fn snapshot(&self) -> VecDeque<T>
+    where
+        T: Clone,
+    {
+        self.with_detached(|content| content.clone())
+    }
+
+    /// Iterate over a [`snapshot()`](Queue::snapshot) of the queue. Modifications made to the queue
+    /// after this call are not reflected by the iterator.
+    pub fn iter_cloned(&self) -> vec_deque::IntoIter<T>
+    where
+        T: Clone,
+    {
+        self.snapshot().into_iter()
+    }
+
     pub fn clear(&self) {
         // SAFETY: `with()` is never invoked recursively
         unsafe { self.0.with(|inner| inner.clear()) }
@@ -61,6 +159,18 @@ impl<T> Queue<T> {
         unsafe { self.0.with(|inner| inner.capacity()) }
     }
 
+    /// Reserve capacity for at least `additional` more items.
+    pub fn reserve(&self, additional: usize) {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.reserve(additional)) }
+    }
+
+    /// Shrink the capacity of the queue as much as possible, e.g. to reclaim memory after a burst.
+    pub fn shrink_to_fit(&self) {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.shrink_to_fit()) }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -68,6 +178,14 @@ impl<T> Queue<T> {
     pub fn into_inner(self) -> VecDeque<T> {
         self.0.into_inner()
     }
+
+    /// Run `f` on the content moved out of `self`, so that `f` may safely access `self` in the meantime.
+    fn with_detached<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut VecDeque<T>) -> R,
+    {
+        self.0.with_detached(f, |content, mut added| content.append(&mut added))
+    }
 }
 
 impl<T> From<VecDeque<T>> for Queue<T> {
@@ -76,6 +194,18 @@ impl<T> From<VecDeque<T>> for Queue<T> {
     }
 }
 
+impl<T> From<Vec<T>> for Queue<T> {
+    fn from(vec: Vec<T>) -> Self {
+        Self(UnsafeWrapper::new(vec.into()))
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        Queue::extend(self, iter)
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Queue<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // SAFETY: `with()` is never invoked recursively
@@ -105,12 +235,161 @@ impl<T> IntoIterator for Queue<T> {
     }
 }
 
+impl<T: Clone> IntoIterator for &Queue<T> {
+    type Item = T;
+    type IntoIter = vec_deque::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_cloned()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use static_assertions::{assert_impl_all, assert_not_impl_any};
+    use std::panic::{self, AssertUnwindSafe};
     use std::{rc::Rc, sync::Arc};
 
+    #[test]
+    fn test_take_all() {
+        let queue = Queue::new();
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+
+        assert_eq!(queue.take_all(), [1, 2, 3]);
+        assert!(queue.is_empty());
+        assert!(queue.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_push_front_pop_back() {
+        let queue = Queue::new();
+        queue.push(2);
+        queue.push_front(1);
+        queue.push(3);
+
+        assert_eq!(queue.pop_back(), Some(3));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop_back(), Some(2));
+        assert_eq!(queue.pop_back(), None);
+    }
+
+    #[test]
+    fn test_remove_first() {
+        let queue = Queue::from(VecDeque::from([1, 2, 3, 4]));
+        assert_eq!(queue.remove_first(|e| e % 2 == 0), Some(2));
+        assert_eq!(queue.remove_first(|e| *e > 10), None);
+        assert_eq!(queue.into_inner(), [1, 3, 4]);
+    }
+
+    #[test]
+    fn test_reserve_and_shrink() {
+        let queue = Queue::new();
+        queue.reserve(100);
+        assert!(queue.capacity() >= 100);
+
+        queue.extend(0..10);
+        queue.shrink_to_fit();
+        assert!(queue.capacity() < 100);
+        assert_eq!(queue.len(), 10);
+    }
+
+    #[test]
+    fn test_swap_remove_first() {
+        let queue = Queue::from(vec![1, 2, 3, 4, 5]);
+        assert_eq!(queue.swap_remove_first(|&e| e % 2 == 0), Some(2));
+        assert_eq!(queue.take_all(), [1, 5, 3, 4]);
+        assert_eq!(queue.swap_remove_first(|_| true), None);
+    }
+
+    #[test]
+    fn test_remove_first_reentrant_access() {
+        let queue = Queue::from(VecDeque::from([1, 2]));
+        let removed = queue.remove_first(|e| {
+            assert!(!queue.contains(e));
+            queue.push(e + 10);
+            *e == 2
+        });
+        assert_eq!(removed, Some(2));
+        assert_eq!(queue.into_inner(), [1, 11, 12]);
+    }
+
+    #[test]
+    fn test_contains_by() {
+        let queue = Queue::from(vec![(1, "a"), (2, "b")]);
+        assert!(queue.contains_by(|(id, _)| *id == 2));
+        assert!(!queue.contains_by(|(_, name)| *name == "c"));
+    }
+
+    #[test]
+    fn test_remove_if() {
+        let queue = Queue::from(VecDeque::from([1, 2, 3, 4]));
+        assert!(queue.remove_if(|e| e % 2 == 0));
+        assert!(!queue.remove_if(|e| *e > 10));
+        assert_eq!(queue.into_inner(), [1, 3]);
+    }
+
+    #[test]
+    fn test_front_map() {
+        let queue = Queue::new();
+        assert_eq!(queue.front_map(|e: &i32| *e), None);
+
+        queue.push(1);
+        queue.push(2);
+        assert_eq!(queue.front_map(|e| *e), Some(1));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut queue = Queue::from(vec![1, 2]);
+        queue.extend([3, 4]);
+        Extend::extend(&mut queue, vec![5]);
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.into_inner(), [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_extend_reentrant_access() {
+        let queue = Queue::from(vec![1]);
+        queue.extend((2..4).inspect(|e| {
+            assert!(!queue.contains(&1));
+            queue.push(e + 10);
+        }));
+        assert_eq!(queue.into_inner(), [1, 2, 3, 12, 13]);
+    }
+
+    #[test]
+    fn test_panicking_closure_keeps_content() {
+        let queue = Queue::from(vec![1, 2]);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            queue.contains_by(|e| {
+                queue.push(e + 10);
+                panic!("boom")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(queue.into_inner(), [1, 2, 11]);
+    }
+
+    #[test]
+    fn test_iterate_snapshot() {
+        let queue = Queue::from(VecDeque::from([1, 2, 3]));
+        let mut iter = queue.iter_cloned();
+        queue.push(4);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.collect::<Vec<_>>(), [2, 3]);
+
+        let mut sum = 0;
+        for e in &queue {
+            sum += e;
+        }
+        assert_eq!(sum, 10);
+        assert_eq!(queue.len(), 4);
+    }
+
     #[test]
     fn test_queue_is_send_but_not_sync() {
         assert_impl_all!(Queue<usize>: std::marker::Send);
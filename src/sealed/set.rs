@@ -30,6 +30,14 @@ impl<T: Eq + Hash> Set<T> {
         unsafe { self.0.with(|inner| inner.insert(value)) }
     }
 
+    /// Insert all elements from `iter`. The set appears empty to any accesses made from within the iterator.
+    pub fn extend<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.with_detached(|content| content.extend(iter))
+    }
+
     pub fn remove<Q>(&self, value: &Q) -> bool
     where
         T: Borrow<Q>,
@@ -39,6 +47,76 @@ impl<T: Eq + Hash> Set<T> {
         unsafe { self.0.with(|inner| inner.remove(value)) }
     }
 
+    /// Remove and return the element equal to `value`, if any.
+    pub fn take<Q>(&self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.take(value)) }
+    }
+
+    /// Clone the element equal to `value`, if any. The set appears empty to any accesses made from within `T::clone()`.
+    pub fn get_cloned<Q>(&self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q> + Clone,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.with_detached(|content| content.get(value).cloned())
+    }
+
+    /// Invoke `f` on each element of the set. The set appears empty to any accesses made from within `f`.
+    pub fn for_each<F>(&self, f: F)
+    where
+        F: FnMut(&T),
+    {
+        self.with_detached(|content| content.iter().for_each(f))
+    }
+
+    /// Retain only the elements for which `f` returns `true`. The set appears empty to any accesses made from within `f`.
+    pub fn retain<F>(&self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.with_detached(|content| content.retain(f))
+    }
+
+    /// Clone the elements that are in `self` but not in `other`.
+    /// Both sets appear empty to any accesses made from within `T::clone()`.
+    pub fn difference_cloned(&self, other: &Set<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.with_both(other, |this, other| this.difference(other).cloned().collect(), Vec::new)
+    }
+
+    /// Clone the elements that are either in `self` or in `other`, but not in both.
+    /// Both sets appear empty to any accesses made from within `T::clone()`.
+    pub fn symmetric_difference_cloned(&self, other: &Set<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.with_both(
+            other,
+            |this, other| this.symmetric_difference(other).cloned().collect(),
+            Vec::new,
+        )
+    }
+
+    /// Clone the elements that are both in `self` and in `other`.
+    /// Both sets appear empty to any accesses made from within `T::clone()`.
+    pub fn intersection_cloned(&self, other: &Set<T>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.with_both(
+            other,
+            |this, other| this.intersection(other).cloned().collect(),
+            || self.snapshot_vec(),
+        )
+    }
+
     pub fn clear(&self) {
         // SAFETY: `with()` is never invoked recursively
         unsafe { self.0.with(|inner| inner.clear()) }
@@ -54,6 +132,18 @@ impl<T: Eq + Hash> Set<T> {
         unsafe { self.0.with(|inner| inner.capacity()) }
     }
 
+    /// Reserve capacity for at least `additional` more items.
+    pub fn reserve(&self, additional: usize) {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.reserve(additional)) }
+    }
+
+    /// Shrink the capacity of the set as much as possible, e.g. to reclaim memory after a burst.
+    pub fn shrink_to_fit(&self) {
+        // SAFETY: `with()` is never invoked recursively
+        unsafe { self.0.with(|inner| inner.shrink_to_fit()) }
+    }
+
     pub fn is_empty(&self) -> bool {
         // SAFETY: `with()` is never invoked recursively
         unsafe { self.0.with(|inner| inner.is_empty()) }
@@ -62,6 +152,35 @@ impl<T: Eq + Hash> Set<T> {
     pub fn into_inner(self) -> HashSet<T> {
         self.0.into_inner()
     }
+
+    fn snapshot_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.with_detached(|content| content.iter().cloned().collect())
+    }
+
+    /// Run `f` on the contents of two different sets, or return `if_same()` if `other` is `self`.
+    /// Both contents are detached, see [`with_detached()`](Self::with_detached).
+    fn with_both<R>(
+        &self,
+        other: &Set<T>,
+        f: impl FnOnce(&HashSet<T>, &HashSet<T>) -> R,
+        if_same: impl FnOnce() -> R,
+    ) -> R {
+        if std::ptr::eq(self, other) {
+            return if_same();
+        }
+        self.with_detached(|this| other.with_detached(|other| f(this, other)))
+    }
+
+    /// Run `f` on the content moved out of `self`, so that `f` may safely access `self` in the meantime.
+    fn with_detached<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut HashSet<T>) -> R,
+    {
+        self.0.with_detached(f, |content, added| content.extend(added))
+    }
 }
 
 impl<T> From<HashSet<T>> for Set<T> {
@@ -70,6 +189,12 @@ impl<T> From<HashSet<T>> for Set<T> {
     }
 }
 
+impl<T: Eq + Hash> Extend<T> for Set<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        Set::extend(self, iter)
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Set<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // SAFETY: `with()` is never invoked recursively
@@ -103,8 +228,161 @@ impl<T> IntoIterator for Set<T> {
 mod tests {
     use super::*;
     use static_assertions::{assert_impl_all, assert_not_impl_any};
+    use std::cell::RefCell;
+    use std::panic::{self, AssertUnwindSafe};
     use std::{rc::Rc, sync::Arc};
 
+    #[test]
+    fn test_for_each() {
+        let set = Set::from(HashSet::from([1, 2, 3]));
+        let mut sum = 0;
+        set.for_each(|e| sum += e);
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn test_retain() {
+        let set = Set::from(HashSet::from([1, 2, 3, 4]));
+        set.retain(|e| e % 2 == 0);
+        assert_eq!(set.into_inner(), HashSet::from([2, 4]));
+    }
+
+    #[test]
+    fn test_take_and_get_cloned() {
+        #[derive(Clone, Debug)]
+        struct Conn {
+            id: u32,
+            addr: &'static str,
+        }
+        impl PartialEq for Conn {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+        impl Eq for Conn {}
+        impl Hash for Conn {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+        impl Borrow<u32> for Conn {
+            fn borrow(&self) -> &u32 {
+                &self.id
+            }
+        }
+
+        let set = Set::new();
+        set.insert(Conn { id: 1, addr: "a" });
+        set.insert(Conn { id: 2, addr: "b" });
+
+        assert_eq!(set.get_cloned(&2).unwrap().addr, "b");
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.take(&1).unwrap().addr, "a");
+        assert!(set.take(&1).is_none());
+        assert!(set.get_cloned(&1).is_none());
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_reserve_and_shrink() {
+        let set = Set::new();
+        set.reserve(100);
+        assert!(set.capacity() >= 100);
+
+        set.extend(0..10);
+        set.shrink_to_fit();
+        assert!(set.capacity() < 100);
+        assert_eq!(set.len(), 10);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut set = Set::from(HashSet::from([1, 2]));
+        set.extend([2, 3]);
+        Extend::extend(&mut set, vec![4]);
+        assert_eq!(set.into_inner(), HashSet::from([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let active = Set::from(HashSet::from([1, 2, 3]));
+        let desired = Set::from(HashSet::from([2, 3, 4]));
+
+        assert_eq!(active.difference_cloned(&desired), [1]);
+        assert_eq!(desired.difference_cloned(&active), [4]);
+
+        let mut diff = active.symmetric_difference_cloned(&desired);
+        diff.sort();
+        assert_eq!(diff, [1, 4]);
+
+        let mut common = active.intersection_cloned(&desired);
+        common.sort();
+        assert_eq!(common, [2, 3]);
+    }
+
+    #[test]
+    fn test_set_operations_detach_both_sets() {
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        struct Probe(u32);
+        thread_local! {
+            static SETS: RefCell<Vec<Rc<Set<Probe>>>> = const { RefCell::new(Vec::new()) };
+        }
+        impl Clone for Probe {
+            fn clone(&self) -> Self {
+                SETS.with_borrow(|sets| assert!(sets.iter().all(|set| set.is_empty())));
+                Probe(self.0)
+            }
+        }
+
+        let active = Rc::new(Set::from(HashSet::from([Probe(1), Probe(2)])));
+        let desired = Rc::new(Set::from(HashSet::from([Probe(2), Probe(3)])));
+        SETS.with_borrow_mut(|sets| sets.extend([active.clone(), desired.clone()]));
+
+        assert_eq!(active.difference_cloned(&desired), [Probe(1)]);
+        assert_eq!(active.intersection_cloned(&desired), [Probe(2)]);
+        assert_eq!(active.symmetric_difference_cloned(&desired).len(), 2);
+        SETS.with_borrow_mut(|sets| sets.truncate(1));
+        assert_eq!(active.intersection_cloned(&active).len(), 2);
+        assert_eq!(active.len(), 2);
+        assert_eq!(desired.len(), 2);
+    }
+
+    #[test]
+    fn test_set_operations_with_itself() {
+        let set = Set::from(HashSet::from([1, 2]));
+        assert!(set.difference_cloned(&set).is_empty());
+        assert!(set.symmetric_difference_cloned(&set).is_empty());
+
+        let mut common = set.intersection_cloned(&set);
+        common.sort();
+        assert_eq!(common, [1, 2]);
+    }
+
+    #[test]
+    fn test_reentrant_access() {
+        let set = Set::from(HashSet::from([1, 2]));
+        set.retain(|e| {
+            assert!(!set.contains(e));
+            set.insert(e + 10);
+            *e != 1
+        });
+        assert_eq!(set.into_inner(), HashSet::from([2, 11, 12]));
+    }
+
+    #[test]
+    fn test_panicking_closure_keeps_content() {
+        let set = Set::from(HashSet::from([1, 2]));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            set.retain(|e| {
+                set.insert(e + 10);
+                panic!("boom")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&1) && set.contains(&2));
+    }
+
     #[test]
     fn test_set_is_send_but_not_sync() {
         assert_impl_all!(Set<usize>: std::marker::Send);
@@ -1,4 +1,5 @@
 use std::cell::UnsafeCell;
+use std::mem;
 
 /// A (hopefully) zero-cost wrapper that simplifies working with unsafe code.
 pub struct UnsafeWrapper<T>(UnsafeCell<T>);
@@ -22,3 +23,39 @@ impl<T> UnsafeWrapper<T> {
         self.0.into_inner()
     }
 }
+
+impl<T: Default> UnsafeWrapper<T> {
+    /// Run `f` on the content moved out of `self`, so that `f` may safely access `self` in the meantime.
+    /// Afterwards `merge` appends anything added to `self` by `f` to the content and puts it back,
+    /// even if `f` panics.
+    pub fn with_detached<R, F>(&self, f: F, merge: fn(&mut T, T)) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        struct Reattach<'a, T: Default> {
+            wrapper: &'a UnsafeWrapper<T>,
+            content: T,
+            merge: fn(&mut T, T),
+        }
+
+        impl<T: Default> Drop for Reattach<'_, T> {
+            fn drop(&mut self) {
+                // SAFETY: `with()` is never invoked recursively
+                unsafe {
+                    self.wrapper.with(|inner| {
+                        let added = mem::replace(inner, mem::take(&mut self.content));
+                        (self.merge)(inner, added);
+                    })
+                }
+            }
+        }
+
+        let mut guard = Reattach {
+            wrapper: self,
+            // SAFETY: `with()` is never invoked recursively
+            content: unsafe { self.with(mem::take) },
+            merge,
+        };
+        f(&mut guard.content)
+    }
+}
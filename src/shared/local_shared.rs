@@ -1,7 +1,9 @@
-use super::{Shared, UnsafeShared};
+use super::{BorrowError, Shared, UnsafeShared};
+#[cfg(debug_assertions)]
+use std::cell::Cell;
 use std::cell::{RefCell, UnsafeCell};
 use std::fmt;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 /// Non-Send wrapper that allows access to the underlying data only through the `Shared` interface.
 pub struct LocalShared<T>(Rc<RefCell<T>>);
@@ -10,6 +12,27 @@ impl<T> LocalShared<T> {
     pub fn new(inner: T) -> Self {
         Self(Rc::new(RefCell::new(inner)))
     }
+
+    /// Create a non-owning handle to the same data, e.g. for breaking reference cycles.
+    pub fn downgrade(&self) -> LocalWeakShared<T> {
+        LocalWeakShared(Rc::downgrade(&self.0))
+    }
+
+    /// Returns `true` if both handles refer to the same underlying data.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.0, &other.0)
+    }
+
+    /// Number of [`LocalShared`] handles to the underlying data, including `self`.
+    pub fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+
+    /// Recover the underlying data if `self` is the only [`LocalShared`] handle to it,
+    /// otherwise give `self` back.
+    pub fn try_unwrap(self) -> Result<T, Self> {
+        Rc::try_unwrap(self.0).map(RefCell::into_inner).map_err(Self)
+    }
 }
 
 impl<T> Shared for LocalShared<T> {
@@ -22,6 +45,22 @@ impl<T> Shared for LocalShared<T> {
     {
         self.0.with(f)
     }
+
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        self.0.try_with(f)
+    }
+
+    #[inline(always)]
+    fn with_ref<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.0.with_ref(f)
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for LocalShared<T> {
@@ -36,12 +75,76 @@ impl<T> Clone for LocalShared<T> {
     }
 }
 
+/// Non-owning version of [`LocalShared`] that doesn't keep the underlying data alive.
+pub struct LocalWeakShared<T>(Weak<RefCell<T>>);
+
+impl<T> LocalWeakShared<T> {
+    /// Get a [`LocalShared`] handle if the underlying data hasn't been dropped yet.
+    pub fn upgrade(&self) -> Option<LocalShared<T>> {
+        self.0.upgrade().map(LocalShared)
+    }
+
+    /// Perform operations on the shared data if it hasn't been dropped yet.
+    pub fn with_opt<R, F>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        self.upgrade().map(|mut shared| shared.with(f))
+    }
+}
+
+impl<T> fmt::Debug for LocalWeakShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LocalWeakShared").finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for LocalWeakShared<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 /// Non-Send wrapper that allows access to the underlying data only through the `UnsafeShared` interface.
-pub struct LocalUnsafeShared<T>(Rc<UnsafeCell<T>>);
+/// In debug builds, panics on nested calls to [`UnsafeShared::with()`] instead of causing undefined behaviour.
+pub struct LocalUnsafeShared<T>(Rc<UnsafeInner<T>>);
+
+struct UnsafeInner<T> {
+    value: UnsafeCell<T>,
+    #[cfg(debug_assertions)]
+    in_use: Cell<bool>,
+}
+
+#[cfg(debug_assertions)]
+struct ReentrancyGuard<'a>(&'a Cell<bool>);
+
+#[cfg(debug_assertions)]
+impl<'a> ReentrancyGuard<'a> {
+    fn new(in_use: &'a Cell<bool>) -> Self {
+        assert!(!in_use.replace(true), "nested access to LocalUnsafeShared");
+        Self(in_use)
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for ReentrancyGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
 
 impl<T> LocalUnsafeShared<T> {
     pub fn new(inner: T) -> Self {
-        Self(Rc::new(UnsafeCell::new(inner)))
+        Self(Rc::new(UnsafeInner {
+            value: UnsafeCell::new(inner),
+            #[cfg(debug_assertions)]
+            in_use: Cell::new(false),
+        }))
+    }
+
+    /// Returns `true` if both handles refer to the same underlying data.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.0, &other.0)
     }
 }
 
@@ -53,13 +156,15 @@ impl<T> UnsafeShared for LocalUnsafeShared<T> {
     where
         F: FnOnce(*mut Self::Target) -> R,
     {
-        self.0.with(f)
+        #[cfg(debug_assertions)]
+        let _guard = ReentrancyGuard::new(&self.0.in_use);
+        f(self.0.value.get())
     }
 }
 
 impl<T: fmt::Debug> fmt::Debug for LocalUnsafeShared<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let value = unsafe { &*self.0.get() };
+        let value = unsafe { &*self.0.value.get() };
         f.debug_tuple("LocalUnsafeShared").field(value).finish()
     }
 }
@@ -89,6 +194,61 @@ mod tests {
         assert_eq!(result, 6);
     }
 
+    #[test]
+    fn test_local_shared_try_with() {
+        let mut shared = LocalShared::new(5);
+        let mut shared_clone = shared.clone();
+
+        let result = shared.with(|data| {
+            *data += 1;
+            shared_clone.try_with(|data| *data)
+        });
+        assert_eq!(result, Err(BorrowError));
+
+        let result = shared_clone.try_with(|data| *data);
+        assert_eq!(result, Ok(6));
+    }
+
+    #[test]
+    fn test_local_shared_with_ref() {
+        let shared = LocalShared::new(vec![1, 2]);
+        let shared_clone = shared.clone();
+
+        let result =
+            shared.with_ref(|outer| shared_clone.with_ref(|inner| outer.len() + inner.len()));
+        assert_eq!(result, 4);
+    }
+
+    #[test]
+    fn test_local_weak_shared() {
+        let shared = LocalShared::new(5);
+        let weak = shared.downgrade();
+
+        assert_eq!(weak.with_opt(|data| *data), Some(5));
+        let mut upgraded = weak.upgrade().unwrap();
+        upgraded.with(|data| *data += 1);
+        assert_eq!(weak.clone().with_opt(|data| *data), Some(6));
+
+        drop(shared);
+        drop(upgraded);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak.with_opt(|data| *data), None);
+    }
+
+    #[test]
+    fn test_local_shared_try_unwrap() {
+        let shared = LocalShared::new(vec![1]);
+        let weak = shared.downgrade();
+        let shared_clone = shared.clone();
+        assert_eq!(shared.strong_count(), 2);
+
+        let shared = shared.try_unwrap().unwrap_err();
+        drop(shared_clone);
+        assert_eq!(shared.strong_count(), 1);
+        assert_eq!(shared.try_unwrap().unwrap(), [1]);
+        assert!(weak.upgrade().is_none());
+    }
+
     #[test]
     fn test_local_unsafe_shared() {
         let mut shared = LocalUnsafeShared::new(10);
@@ -104,4 +264,29 @@ mod tests {
         let result = unsafe { shared_clone.with(|data| *data) };
         assert_eq!(result, 11);
     }
+
+    #[test]
+    fn test_ptr_eq() {
+        let shared = LocalShared::new(1);
+        assert!(LocalShared::ptr_eq(&shared, &shared.clone()));
+        assert!(!LocalShared::ptr_eq(&shared, &LocalShared::new(1)));
+
+        let shared = LocalUnsafeShared::new(1);
+        assert!(LocalUnsafeShared::ptr_eq(&shared, &shared.clone()));
+        assert!(!LocalUnsafeShared::ptr_eq(&shared, &LocalUnsafeShared::new(1)));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "nested access to LocalUnsafeShared")]
+    fn test_local_unsafe_shared_detects_nested_access() {
+        let mut shared = LocalUnsafeShared::new(10);
+        let mut shared_clone = shared.clone();
+
+        unsafe {
+            shared.with_unchecked(|_| {
+                shared_clone.with_unchecked(|_| {});
+            })
+        };
+    }
 }
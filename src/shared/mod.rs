@@ -4,11 +4,11 @@ pub mod local_shared;
 pub mod projected_shared;
 
 use std::cell::UnsafeCell;
-use std::sync::{Arc, Mutex, PoisonError};
-use std::{cell::RefCell, rc::Rc};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, TryLockError};
+use std::{cell::RefCell, fmt, mem, rc::Rc};
 
-pub use local_shared::LocalShared;
-pub use projected_shared::ProjectedShared;
+pub use local_shared::{LocalShared, LocalWeakShared};
+pub use projected_shared::{ProjectedShared, ProjectedShared2};
 
 /// An abstraction for accessing data shared between multiple tasks. In particular, this helps prevent
 /// holding references to such data across suspension points.
@@ -20,6 +20,59 @@ pub trait Shared: Clone {
     where
         F: FnOnce(&mut Self::Target) -> R;
 
+    /// Perform operations on the shared data, or return an error instead of panicking or blocking
+    /// if the data is already being accessed. All implementations in this crate do so, but the default
+    /// implementation simply calls [`with()`](Self::with) and never fails, so implementors that can detect
+    /// concurrent access must override it.
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        Ok(self.with(f))
+    }
+
+    /// Perform read-only operations on the shared data.
+    #[inline(always)]
+    fn with_ref<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Target) -> R,
+    {
+        self.clone().with(|target| f(target))
+    }
+
+    /// Replace the shared data with `value`, returning the old value.
+    /// Not named `replace()` so as not to shadow e.g. [`RefCell::replace()`] on `Rc<RefCell<T>>`.
+    #[inline(always)]
+    fn replace_value(&mut self, value: Self::Target) -> Self::Target {
+        self.with(|target| mem::replace(target, value))
+    }
+
+    /// Replace the shared data with its default value, returning the old value.
+    /// Not named `take()` so as not to shadow e.g. [`RefCell::take()`] on `Rc<RefCell<T>>`.
+    #[inline(always)]
+    fn take_value(&mut self) -> Self::Target
+    where
+        Self::Target: Default,
+    {
+        self.with(mem::take)
+    }
+
+    /// Get a copy of the shared data.
+    #[inline(always)]
+    fn get(&mut self) -> Self::Target
+    where
+        Self::Target: Copy,
+    {
+        self.with(|target| *target)
+    }
+
+    /// Overwrite the shared data with `value`.
+    #[inline(always)]
+    fn set(&mut self, value: Self::Target) {
+        self.with(|target| *target = value)
+    }
+
     /// Get a `Shared` object for accessing part of `self`
     fn project<To, Proj>(&self, f: Proj) -> ProjectedShared<Self, Proj>
     where
@@ -30,6 +83,17 @@ pub trait Shared: Clone {
             proj_fn: f,
         }
     }
+
+    /// Get an object for accessing two disjoint parts of `self` at the same time
+    fn project2<A, B, Proj>(&self, f: Proj) -> ProjectedShared2<Self, Proj>
+    where
+        Proj: Fn(&mut Self::Target) -> (&mut A, &mut B) + Clone,
+    {
+        ProjectedShared2 {
+            inner: self.clone(),
+            proj_fn: f,
+        }
+    }
 }
 
 impl<T> Shared for Rc<RefCell<T>> {
@@ -42,6 +106,23 @@ impl<T> Shared for Rc<RefCell<T>> {
     {
         f(&mut self.borrow_mut())
     }
+
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        let mut guard = self.try_borrow_mut().map_err(|_| BorrowError)?;
+        Ok(f(&mut guard))
+    }
+
+    #[inline(always)]
+    fn with_ref<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Target) -> R,
+    {
+        f(&self.borrow())
+    }
 }
 
 impl<T> Shared for Arc<Mutex<T>> {
@@ -54,8 +135,74 @@ impl<T> Shared for Arc<Mutex<T>> {
     {
         f(&mut self.lock().unwrap_or_else(PoisonError::into_inner))
     }
+
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        let mut guard = match self.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(BorrowError),
+        };
+        Ok(f(&mut guard))
+    }
+
+    #[inline(always)]
+    fn with_ref<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Target) -> R,
+    {
+        f(&self.lock().unwrap_or_else(PoisonError::into_inner))
+    }
 }
 
+impl<T> Shared for Arc<RwLock<T>> {
+    type Target = T;
+
+    #[inline(always)]
+    fn with<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        f(&mut self.write().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        let mut guard = match self.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(BorrowError),
+        };
+        Ok(f(&mut guard))
+    }
+
+    #[inline(always)]
+    fn with_ref<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self::Target) -> R,
+    {
+        f(&self.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Error returned from [`Shared::try_with()`] when the shared data is already being accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorrowError;
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("shared data is already borrowed")
+    }
+}
+
+impl std::error::Error for BorrowError {}
+
 /// An unsafe abstraction for accessing data shared between multiple tasks. In particular,
 /// this helps prevent holding references to such data across suspension points.
 pub trait UnsafeShared: Clone {
@@ -154,3 +301,108 @@ macro_rules! define_with_unchecked {
         }
     };
 }
+
+/// Project a [`Shared`] or [`UnsafeShared`] object onto one of its fields, without having to write
+/// the projection closure by hand. Use `unsafe` before the object for [`UnsafeShared`] projections.
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use std::{cell::{RefCell, UnsafeCell}, rc::Rc};
+/// struct Inner { counter: u32 }
+/// struct Outer { name: String, inner: Inner }
+///
+/// let shared = LocalShared::new(Outer { name: "outer".into(), inner: Inner { counter: 1 } });
+/// let mut counter = project_field!(shared, inner.counter);
+/// counter.with(|counter| *counter += 1);
+///
+/// let unsafe_shared = Rc::new(UnsafeCell::new((0u8, 1u32)));
+/// let mut second = project_field!(unsafe unsafe_shared, 1);
+/// assert_eq!(unsafe { second.with_unchecked(|value| *value) }, 1);
+/// ```
+/// Only field accesses are accepted:
+/// ```compile_fail
+/// # use local_async_utils::prelude::*;
+/// let shared = LocalShared::new(vec![1, 2, 3]);
+/// let projected = project_field!(shared, len());
+/// ```
+#[macro_export]
+macro_rules! project_field {
+    (unsafe $shared:expr, $($field:tt).+) => {
+        $crate::shared::UnsafeShared::project(
+            &$shared,
+            // SAFETY: the pointer comes from `UnsafeShared::with()`, so it points to a valid object
+            |target| unsafe { &raw mut (*target).$($field).+ },
+        )
+    };
+    ($shared:expr, $($field:tt).+) => {
+        $crate::shared::Shared::project(&$shared, |target| &mut target.$($field).+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rwlock_shared() {
+        let mut shared = Arc::new(RwLock::new(5));
+        shared.with(|data| *data += 1);
+
+        let result = shared.with_ref(|data| *data);
+        assert_eq!(result, 6);
+
+        let result = shared.with_ref(|_| shared.clone().try_with(|data| *data));
+        assert_eq!(result, Err(BorrowError));
+    }
+
+    #[test]
+    fn test_project_field() {
+        struct Inner(Vec<u32>, u32);
+        struct Outer {
+            inner: Inner,
+        }
+
+        let mut shared = Rc::new(RefCell::new(Outer {
+            inner: Inner(vec![], 0),
+        }));
+        let mut projected = project_field!(shared, inner.0);
+        projected.with(|vec| vec.push(1));
+        let mut projected = project_field!(shared, inner.1);
+        projected.set(2);
+        assert_eq!(shared.with(|outer| (outer.inner.0.clone(), outer.inner.1)), (vec![1], 2));
+
+        let mut shared = Rc::new(UnsafeCell::new(Outer {
+            inner: Inner(vec![], 0),
+        }));
+        let mut projected = project_field!(unsafe shared, inner.1);
+        unsafe { projected.with_unchecked(|value| *value = 5) };
+        assert_eq!(unsafe { shared.with_unchecked(|outer| outer.inner.1) }, 5);
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut counter = LocalShared::new(0u64);
+        let value = counter.get();
+        counter.set(value + 1);
+        assert_eq!(counter.get(), 1);
+
+        let mut flag = Arc::new(RwLock::new(false));
+        flag.set(true);
+        assert!(flag.get());
+    }
+
+    #[test]
+    fn test_replace_value_and_take_value() {
+        let mut shared = Rc::new(RefCell::new(vec![1, 2]));
+        assert_eq!(shared.replace_value(vec![3]), [1, 2]);
+        assert_eq!(shared.take_value(), [3]);
+        assert!(shared.with_ref(Vec::is_empty));
+        // inherent methods of the inner type are not shadowed
+        assert_eq!(shared.replace(vec![4]), []);
+        assert_eq!(shared.take(), [4]);
+
+        let mut shared = Arc::new(Mutex::new(String::from("a")));
+        assert_eq!(shared.replace_value(String::from("b")), "a");
+        assert_eq!(shared.take_value(), "b");
+        assert_eq!(shared.take_value(), "");
+    }
+}
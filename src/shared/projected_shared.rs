@@ -1,4 +1,4 @@
-use super::{Shared, UnsafeShared};
+use super::{BorrowError, Shared, UnsafeShared};
 use std::fmt;
 
 pub struct ProjectedShared<T, F> {
@@ -21,6 +21,61 @@ where
         let proj_fn = &self.proj_fn;
         self.inner.with(|from| f(proj_fn(from)))
     }
+
+    #[inline(always)]
+    fn try_with<R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        F: FnOnce(&mut Self::Target) -> R,
+    {
+        let proj_fn = &self.proj_fn;
+        self.inner.try_with(|from| f(proj_fn(from)))
+    }
+}
+
+impl<From, Mid, Inner, Proj> ProjectedShared<Inner, Proj>
+where
+    Inner: Shared<Target = From>,
+    Proj: Fn(&mut From) -> &mut Mid + Clone,
+{
+    /// Get a `Shared` object for accessing part of `self`. Unlike [`Shared::project()`], this composes
+    /// both projections into one instead of nesting `ProjectedShared` objects.
+    pub fn project_chain<To, Next>(
+        &self,
+        f: Next,
+    ) -> ProjectedShared<
+        Inner,
+        impl Fn(&mut From) -> &mut To + Clone + use<From, Mid, To, Inner, Proj, Next>,
+    >
+    where
+        Next: Fn(&mut Mid) -> &mut To + Clone,
+    {
+        let proj_fn = self.proj_fn.clone();
+        ProjectedShared {
+            inner: self.inner.clone(),
+            proj_fn: compose(proj_fn, f),
+        }
+    }
+}
+
+fn compose<A, B, C, F, G>(f: F, g: G) -> impl Fn(&mut A) -> &mut C + Clone
+where
+    F: Fn(&mut A) -> &mut B + Clone,
+    G: Fn(&mut B) -> &mut C + Clone,
+{
+    // SAFETY: both pointers are derived from `a` via the projection functions, so the result
+    // has the same provenance and lifetime as `a`
+    move |a| unsafe { &mut *project_raw(&g, project_raw(&f, a)) }
+}
+
+// Going through a raw pointer avoids naming `&mut B` inside a higher-ranked closure,
+// which would require `B: 'static`.
+#[inline(always)]
+fn project_raw<A, B, F>(f: &F, a: *mut A) -> *mut B
+where
+    F: Fn(&mut A) -> &mut B,
+{
+    // SAFETY: `a` is always derived from a valid unique reference
+    f(unsafe { &mut *a })
 }
 
 impl<T, F> Clone for ProjectedShared<T, F>
@@ -59,6 +114,64 @@ impl<T, F> fmt::Debug for ProjectedShared<T, F> {
     }
 }
 
+pub struct ProjectedShared2<T, F> {
+    pub(super) inner: T,
+    pub(super) proj_fn: F,
+}
+
+impl<From, Inner, Proj> ProjectedShared2<Inner, Proj>
+where
+    Inner: Shared<Target = From>,
+{
+    /// Perform operations on two disjoint parts of the shared data.
+    #[inline(always)]
+    pub fn with2<A, B, R, F>(&mut self, f: F) -> R
+    where
+        Proj: Fn(&mut From) -> (&mut A, &mut B),
+        F: FnOnce(&mut A, &mut B) -> R,
+    {
+        let proj_fn = &self.proj_fn;
+        self.inner.with(|from| {
+            let (a, b) = proj_fn(from);
+            f(a, b)
+        })
+    }
+
+    /// Perform operations on two disjoint parts of the shared data, or return an error if the data is already
+    /// being accessed. See [`Shared::try_with()`].
+    #[inline(always)]
+    pub fn try_with2<A, B, R, F>(&mut self, f: F) -> Result<R, BorrowError>
+    where
+        Proj: Fn(&mut From) -> (&mut A, &mut B),
+        F: FnOnce(&mut A, &mut B) -> R,
+    {
+        let proj_fn = &self.proj_fn;
+        self.inner.try_with(|from| {
+            let (a, b) = proj_fn(from);
+            f(a, b)
+        })
+    }
+}
+
+impl<T, F> Clone for ProjectedShared2<T, F>
+where
+    T: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            proj_fn: self.proj_fn.clone(),
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for ProjectedShared2<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProjectedShared2").finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +189,45 @@ mod tests {
         let result = shared.with(|data| data.0);
         assert_eq!(result, 11);
     }
+
+    #[test]
+    fn test_project_chain() {
+        struct C(i32);
+        struct B(C);
+        struct A(B);
+
+        let mut shared = LocalShared::new(A(B(C(1))));
+        let mut projected = shared.project(|a| &mut a.0).project_chain(|b| &mut b.0.0);
+
+        projected.with(|c| *c += 10);
+
+        let result = shared.with(|a| a.0.0.0);
+        assert_eq!(result, 11);
+    }
+
+    #[test]
+    fn test_projected_shared2() {
+        let mut shared = LocalShared::new((vec![1], 2, 3));
+        let mut projected = shared.project2(|data| (&mut data.0, &mut data.2));
+
+        projected.with2(|vec, counter| {
+            vec.push(*counter);
+            *counter += 1;
+        });
+
+        let result = shared.with(|data| data.clone());
+        assert_eq!(result, (vec![1, 3], 2, 4));
+    }
+
+    #[test]
+    fn test_projected_shared2_try_with2() {
+        let shared = std::rc::Rc::new(std::cell::RefCell::new((1, 2)));
+        let mut projected = shared.project2(|data| (&mut data.0, &mut data.1));
+
+        let result = shared.with_ref(|_| projected.try_with2(|a, b| *a + *b));
+        assert_eq!(result, Err(BorrowError));
+
+        let result = projected.try_with2(|a, b| *a + *b);
+        assert_eq!(result, Ok(3));
+    }
 }
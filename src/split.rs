@@ -1,4 +1,4 @@
-//! Utilities for splitting `AsyncRead + AsyncWrite` types into separate read and write halves.
+//! Utilities for splitting `AsyncRead` and/or `AsyncWrite` types into separate handles.
 
 use std::cell::RefCell;
 use std::pin::Pin;
@@ -7,10 +7,10 @@ use std::task::{Context, Poll};
 use std::{fmt, io};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
-/// The readable half of a value returned from [`split`].
+/// The readable half of a value returned from [`split`] or [`split_read`].
 pub struct ReadHalf<T: AsyncRead>(Rc<RefCell<T>>);
 
-/// The writable half of a value returned from [`split`].
+/// The writable half of a value returned from [`split`] or [`split_write`].
 pub struct WriteHalf<T: AsyncWrite>(Rc<RefCell<T>>);
 
 /// Splits a single value implementing `AsyncRead + AsyncWrite` into separate `AsyncRead` and `AsyncWrite` handles.
@@ -20,6 +20,110 @@ pub fn split<T: AsyncRead + AsyncWrite>(value: T) -> (ReadHalf<T>, WriteHalf<T>)
     (ReadHalf(shared.clone()), WriteHalf(shared))
 }
 
+/// Shares a single `AsyncRead` value between two read handles, e.g. a reader task and a task that
+/// occasionally drains the stream. Each `poll_*` call borrows the value exclusively for its duration only,
+/// and the data is split between the handles at `poll_read` granularity.
+///
+/// Only one of the handles may wait for data at a time: most `AsyncRead` implementations (including
+/// [`ReadEnd`](crate::sync::pipe::ReadEnd)) store a single read waker, so if both handles are pending in different
+/// tasks, only the task that polled last is woken.
+/// Panics if the value's own `poll_*` implementation re-enters one of the handles.
+pub fn split_read<T: AsyncRead>(value: T) -> (ReadHalf<T>, ReadHalf<T>) {
+    let shared = Rc::new(RefCell::new(value));
+    (ReadHalf(shared.clone()), ReadHalf(shared))
+}
+
+/// Shares a single `AsyncWrite` value between two write handles, e.g. a writer task and a control task that
+/// calls `poll_shutdown`. Each `poll_*` call borrows the value exclusively for its duration only,
+/// and concurrent writes are interleaved at `poll_write` granularity.
+///
+/// Unless `T` supports several waiting writers (like [`WriteEnd`](crate::sync::pipe::WriteEnd) does), only one
+/// of the handles may wait for the value to become writable at a time, otherwise only the task that polled last is woken.
+/// Panics if the value's own `poll_*` implementation re-enters one of the handles.
+pub fn split_write<T: AsyncWrite>(value: T) -> (WriteHalf<T>, WriteHalf<T>) {
+    let shared = Rc::new(RefCell::new(value));
+    (WriteHalf(shared.clone()), WriteHalf(shared))
+}
+
+/// Reunites the halves returned from [`split`] back into the original value.
+/// Non-thread-safe equivalent of [`tokio::io::ReadHalf::unsplit`](https://docs.rs/tokio/latest/tokio/io/struct.ReadHalf.html#method.unsplit).
+/// # Returns
+/// - `Ok(value)` if both halves originate from the same [`split`] call.
+/// - `Err((read, write))` with the halves unchanged otherwise.
+///
+/// `T` must be `Unpin`, because the halves poll the value through a pinned reference and it may not be moved out afterwards:
+/// ```compile_fail
+/// # use local_async_utils::split::{split, unsplit};
+/// # use std::{io, marker::PhantomPinned, pin::Pin, task::{Context, Poll}};
+/// # use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+/// struct NotUnpin(PhantomPinned);
+/// # impl AsyncRead for NotUnpin {
+/// #     fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// # }
+/// # impl AsyncWrite for NotUnpin {
+/// #     fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+/// #         Poll::Ready(Ok(buf.len()))
+/// #     }
+/// #     fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// #     fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// # }
+/// let (read, write) = split(NotUnpin(PhantomPinned));
+/// let _ = unsplit(read, write);
+/// ```
+pub fn unsplit<T: AsyncRead + AsyncWrite + Unpin>(
+    read: ReadHalf<T>,
+    write: WriteHalf<T>,
+) -> Result<T, (ReadHalf<T>, WriteHalf<T>)> {
+    if !read.is_pair_of(&write) {
+        return Err((read, write));
+    }
+    debug_assert_eq!(Rc::strong_count(&read.0), 2, "unexpected extra handles");
+    let WriteHalf(write_shared) = write;
+    drop(write_shared);
+    let ReadHalf(shared) = read;
+    Rc::try_unwrap(shared)
+        .map(RefCell::into_inner)
+        .map_err(|shared| (ReadHalf(shared.clone()), WriteHalf(shared)))
+}
+
+impl<T: AsyncRead> ReadHalf<T> {
+    /// Checks whether `self` and `other` originate from the same [`split`] call.
+    pub fn is_pair_of(&self, other: &WriteHalf<T>) -> bool
+    where
+        T: AsyncWrite,
+    {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Number of handles sharing the underlying value, including `self`.
+    /// Returns 1 if the other half has been dropped.
+    pub fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+}
+
+impl<T: AsyncWrite> WriteHalf<T> {
+    /// Checks whether `self` and `other` originate from the same [`split`] call.
+    pub fn is_pair_of(&self, other: &ReadHalf<T>) -> bool
+    where
+        T: AsyncRead,
+    {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Number of handles sharing the underlying value, including `self`.
+    /// Returns 1 if the other half has been dropped.
+    pub fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+}
+
 fn with_pin<T, R>(half: &RefCell<T>, f: impl FnOnce(Pin<&mut T>) -> R) -> R {
     let mut guard = half.borrow_mut();
 
@@ -80,3 +184,138 @@ impl<T: fmt::Debug + AsyncWrite> fmt::Debug for WriteHalf<T> {
         f.debug_tuple("WriteHalf").field(&self.0.borrow()).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::pipe::{Pipe, duplex_pipe};
+    use static_assertions::{assert_impl_all, assert_not_impl_any};
+    use std::marker::PhantomPinned;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_test::{assert_pending, assert_ready, task::spawn};
+
+    struct NotUnpin(PhantomPinned);
+
+    impl AsyncRead for NotUnpin {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            buf.put_slice(b"x");
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for NotUnpin {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize, io::Error>> {
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), io::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_is_pair_of() {
+        let (stream1, stream2) = duplex_pipe(16);
+        let (read1, write1) = split(stream1);
+        let (read2, write2) = split(stream2);
+
+        assert!(read1.is_pair_of(&write1));
+        assert!(write1.is_pair_of(&read1));
+        assert!(!read1.is_pair_of(&write2));
+        assert!(!write2.is_pair_of(&read1));
+        assert!(read2.is_pair_of(&write2));
+    }
+
+    #[test]
+    fn test_strong_count() {
+        let (stream, _other) = duplex_pipe(16);
+        let (read, write) = split(stream);
+        assert_eq!(read.strong_count(), 2);
+        assert_eq!(write.strong_count(), 2);
+
+        drop(read);
+        assert_eq!(write.strong_count(), 1);
+    }
+
+    #[test]
+    fn test_unsplit_paired_halves() {
+        let (stream, _other) = duplex_pipe(16);
+        let (read, write) = split(stream);
+        assert!(unsplit(read, write).is_ok());
+    }
+
+    #[test]
+    fn test_unsplit_unpaired_halves() {
+        let (stream1, stream2) = duplex_pipe(16);
+        let (read1, write1) = split(stream1);
+        let (read2, write2) = split(stream2);
+
+        let (read1, write2) = unsplit(read1, write2).unwrap_err();
+        let (read2, write1) = unsplit(read2, write1).unwrap_err();
+
+        assert!(unsplit(read1, write1).is_ok());
+        assert!(unsplit(read2, write2).is_ok());
+    }
+
+    #[test]
+    fn test_split_accepts_non_unpin_values() {
+        assert_impl_all!(NotUnpin: AsyncRead, AsyncWrite);
+        assert_not_impl_any!(NotUnpin: Unpin);
+        // such values can be split, but not unsplit, see the `compile_fail` example on `unsplit()`
+
+        let (mut read, mut write) = split(NotUnpin(PhantomPinned));
+        let mut buf = [0u8; 1];
+        assert_ready!(spawn(read.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"x");
+        assert_ready!(spawn(write.write_all(b"abc")).poll()).unwrap();
+    }
+
+    #[test]
+    fn test_split_write_shares_writer() {
+        let (mut reader, writer) = Pipe::new(16).into_split();
+        let (mut data_writer, mut control_writer) = split_write(writer);
+
+        assert_ready!(spawn(data_writer.write_all(b"abc")).poll()).unwrap();
+        assert_ready!(spawn(control_writer.write_all(b"def")).poll()).unwrap();
+        assert_ready!(spawn(control_writer.shutdown()).poll()).unwrap();
+
+        let mut buf = Vec::new();
+        assert_ready!(spawn(reader.read_to_end(&mut buf)).poll()).unwrap();
+        assert_eq!(buf, b"abcdef");
+        assert!(assert_ready!(spawn(data_writer.write(b"x")).poll()).is_err());
+    }
+
+    #[test]
+    fn test_split_read_shares_reader() {
+        let (reader, mut writer) = Pipe::new(16).into_split();
+        let (mut reader1, mut reader2) = split_read(reader);
+
+        let mut buf = [0u8; 2];
+        let mut read = spawn(reader1.read_exact(&mut buf));
+        assert_pending!(read.poll());
+
+        assert_ready!(spawn(writer.write_all(b"abcd")).poll()).unwrap();
+        assert!(read.is_woken());
+        assert_ready!(read.poll()).unwrap();
+        drop(read);
+        assert_eq!(&buf, b"ab");
+
+        assert_ready!(spawn(reader2.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"cd");
+    }
+}
@@ -1,6 +1,9 @@
 //! Utilities for measuring the duration of operations and logging if they exceed a specified threshold.
 
-use std::fmt;
+use crate::shared::{LocalShared, Shared};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
 use std::time::Instant;
 
 #[cfg(feature = "tokio")]
@@ -16,6 +19,8 @@ pub struct Stopwatch {
     starttime: Instant,
     location: &'static str,
     what: String,
+    paused_total: Duration,
+    paused_at: Option<Instant>,
 }
 
 impl Stopwatch {
@@ -31,13 +36,58 @@ impl Stopwatch {
             starttime: Instant::now(),
             location,
             what: fmt::format(args),
+            paused_total: Duration::ZERO,
+            paused_at: None,
         }
     }
+
+    /// Time elapsed since the creation of this `Stopwatch`, excluding the time it has been paused.
+    pub fn elapsed(&self) -> Duration {
+        let paused_now = self.paused_at.map_or(Duration::ZERO, |at| at.elapsed());
+        self.starttime.elapsed().saturating_sub(self.paused_total + paused_now)
+    }
+
+    /// Stop counting time until [`resume()`](Stopwatch::resume) is called, e.g. while waiting for an external
+    /// resource that shouldn't be accounted for. Does nothing if already paused.
+    /// ```
+    /// use local_async_utils::prelude::*;
+    ///
+    /// let mut sw = info_stopwatch!(millisec!(50), "Rate-limited operation");
+    /// sw.pause();
+    /// std::thread::sleep(millisec!(100)); // waiting for the rate limiter
+    /// sw.resume();
+    /// assert!(sw.elapsed() < millisec!(50));
+    /// drop(sw); // Doesn't log
+    /// ```
+    pub fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Continue counting time after [`pause()`](Stopwatch::pause). Does nothing if not paused.
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += paused_at.elapsed();
+        }
+    }
+
+    /// Log the time elapsed so far, regardless of the threshold.
+    /// ```
+    /// use local_async_utils::prelude::*;
+    ///
+    /// let sw = info_stopwatch!(sec!(1), "Two-phase operation");
+    /// std::thread::sleep(millisec!(10));
+    /// sw.lap("phase 1"); // Logs: "Two-phase operation: phase 1 at 10ms"
+    /// assert!(sw.elapsed() >= millisec!(10));
+    /// ```
+    pub fn lap(&self, label: &str) {
+        let duration = self.elapsed();
+        log::log!(target: self.location, self.lvl, "{}: {} at {:?}", self.what, label, duration);
+    }
 }
 
 impl Drop for Stopwatch {
     fn drop(&mut self) {
-        let duration = self.starttime.elapsed();
+        let duration = self.elapsed();
         if duration > self.threshold {
             log::log!(target: self.location, self.lvl, "{} finished in {:?}", self.what, duration);
         }
@@ -50,6 +100,293 @@ impl fmt::Debug for Stopwatch {
     }
 }
 
+/// Utility for measuring the duration of a fallible operation. When dropped, it will log the time elapsed since its
+/// creation only if the operation ended in the error state and took longer than the threshold.
+/// The initial state is error, so that early returns (e.g. via `?`) are treated as failures;
+/// call [`mark_ok()`](ResultStopwatch::mark_ok) once the operation has succeeded.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// fn fallible(fail: bool) -> Result<(), ()> {
+///     let sw = error_stopwatch_on_err!(sec!(0), "Fallible operation");
+///     if fail {
+///         return Err(()); // Logs: "Fallible operation failed in <elapsed>"
+///     }
+///     sw.mark_ok(); // Doesn't log
+///     Ok(())
+/// }
+/// # fallible(true).unwrap_err();
+/// # fallible(false).unwrap();
+/// ```
+pub struct ResultStopwatch {
+    lvl: log::Level,
+    threshold: Duration,
+    starttime: Instant,
+    location: &'static str,
+    what: String,
+    succeeded: Cell<bool>,
+}
+
+impl ResultStopwatch {
+    pub fn new(
+        lvl: log::Level,
+        threshold: Duration,
+        location: &'static str,
+        args: fmt::Arguments,
+    ) -> Self {
+        Self {
+            lvl,
+            threshold,
+            starttime: Instant::now(),
+            location,
+            what: fmt::format(args),
+            succeeded: Cell::new(false),
+        }
+    }
+
+    /// Time elapsed since the creation of this `ResultStopwatch`.
+    pub fn elapsed(&self) -> Duration {
+        self.starttime.elapsed()
+    }
+
+    /// Mark the operation as successful, nothing will be logged on drop.
+    pub fn mark_ok(&self) {
+        self.succeeded.set(true);
+    }
+
+    /// Mark the operation as failed, the elapsed time will be logged on drop if it exceeds the threshold.
+    pub fn mark_err(&self) {
+        self.succeeded.set(false);
+    }
+}
+
+impl Drop for ResultStopwatch {
+    fn drop(&mut self) {
+        let duration = self.elapsed();
+        if !self.succeeded.get() && duration > self.threshold {
+            log::log!(target: self.location, self.lvl, "{} failed in {:?}", self.what, duration);
+        }
+    }
+}
+
+impl fmt::Debug for ResultStopwatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResultStopwatch").finish()
+    }
+}
+
+/// Utility for measuring the duration of an operation. When dropped, it will invoke the callback with the time elapsed
+/// since its creation if it exceeds the threshold. Unlike [`Stopwatch`], it doesn't depend on `log`.
+/// ```
+/// use local_async_utils::prelude::*;
+/// use std::{cell::Cell, rc::Rc};
+///
+/// let measured = Rc::new(Cell::new(None));
+/// let sw = StopwatchWith::new(sec!(0), {
+///     let measured = measured.clone();
+///     move |elapsed| measured.set(Some(elapsed))
+/// });
+/// std::thread::sleep(millisec!(10));
+/// drop(sw);
+/// assert!(measured.get().unwrap() >= millisec!(10));
+/// ```
+pub struct StopwatchWith<F: FnOnce(Duration)> {
+    threshold: Duration,
+    starttime: Instant,
+    callback: Option<F>,
+}
+
+impl<F: FnOnce(Duration)> StopwatchWith<F> {
+    pub fn new(threshold: Duration, callback: F) -> Self {
+        Self {
+            threshold,
+            starttime: Instant::now(),
+            callback: Some(callback),
+        }
+    }
+
+    /// Time elapsed since the creation of this `StopwatchWith`.
+    pub fn elapsed(&self) -> Duration {
+        self.starttime.elapsed()
+    }
+}
+
+impl<F: FnOnce(Duration)> Drop for StopwatchWith<F> {
+    fn drop(&mut self) {
+        let duration = self.elapsed();
+        if duration > self.threshold
+            && let Some(callback) = self.callback.take()
+        {
+            callback(duration);
+        }
+    }
+}
+
+impl<F: FnOnce(Duration)> fmt::Debug for StopwatchWith<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StopwatchWith").finish_non_exhaustive()
+    }
+}
+
+/// Aggregated durations of all measurements of a scope in a [`StopwatchRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeStats {
+    pub count: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl ScopeStats {
+    fn new(elapsed: Duration) -> Self {
+        Self {
+            count: 1,
+            total: elapsed,
+            min: elapsed,
+            max: elapsed,
+        }
+    }
+
+    fn add(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.min = self.min.min(elapsed);
+        self.max = self.max.max(elapsed);
+    }
+
+    /// Average duration of a measurement.
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos((self.total.as_nanos() / u128::from(self.count)) as u64)
+    }
+}
+
+/// Collection of durations of named scopes, for profiling code that runs too often to log each
+/// measurement separately. Clones refer to the same registry.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// let registry = StopwatchRegistry::new();
+/// for _ in 0..3 {
+///     let _sw = aggregate_stopwatch!(registry, "iteration");
+///     std::thread::sleep(millisec!(1));
+/// }
+/// assert_eq!(registry.stats("iteration").unwrap().count, 3);
+/// log::info!("{}", registry.report()); // Logs: "iteration: count=3 min=1ms mean=1ms max=1ms"
+/// ```
+#[derive(Clone)]
+pub struct StopwatchRegistry(LocalShared<HashMap<&'static str, ScopeStats>>);
+
+impl StopwatchRegistry {
+    pub fn new() -> Self {
+        Self(LocalShared::new(HashMap::new()))
+    }
+
+    /// Start measuring a scope. The elapsed time is added to the registry when the returned guard is dropped.
+    pub fn start(&self, label: &'static str) -> AggregateStopwatch {
+        AggregateStopwatch {
+            registry: self.clone(),
+            label,
+            starttime: Instant::now(),
+        }
+    }
+
+    /// Add a single measurement of the scope `label`.
+    pub fn record(&mut self, label: &'static str, elapsed: Duration) {
+        self.0.with(|scopes| {
+            scopes
+                .entry(label)
+                .and_modify(|stats| stats.add(elapsed))
+                .or_insert_with(|| ScopeStats::new(elapsed));
+        })
+    }
+
+    /// Aggregated measurements of the scope `label`, if any.
+    pub fn stats(&self, label: &str) -> Option<ScopeStats> {
+        self.0.with_ref(|scopes| scopes.get(label).copied())
+    }
+
+    /// Human-readable summary of all scopes in alphabetical order, one line per scope.
+    pub fn report(&self) -> String {
+        self.0.with_ref(|scopes| {
+            let mut labels: Vec<_> = scopes.keys().copied().collect();
+            labels.sort_unstable();
+            let mut report = String::new();
+            for label in labels {
+                let stats = &scopes[label];
+                let _ = writeln!(
+                    report,
+                    "{label}: count={} min={:?} mean={:?} max={:?}",
+                    stats.count,
+                    stats.min,
+                    stats.mean(),
+                    stats.max
+                );
+            }
+            report
+        })
+    }
+}
+
+impl Default for StopwatchRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for StopwatchRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StopwatchRegistry").field(&self.0).finish()
+    }
+}
+
+/// Guard created by [`StopwatchRegistry::start()`] that adds the time elapsed since its creation to the
+/// registry when dropped.
+pub struct AggregateStopwatch {
+    registry: StopwatchRegistry,
+    label: &'static str,
+    starttime: Instant,
+}
+
+impl AggregateStopwatch {
+    /// Time elapsed since the creation of this `AggregateStopwatch`.
+    pub fn elapsed(&self) -> Duration {
+        self.starttime.elapsed()
+    }
+}
+
+impl Drop for AggregateStopwatch {
+    fn drop(&mut self) {
+        let duration = self.elapsed();
+        self.registry.record(self.label, duration);
+    }
+}
+
+impl fmt::Debug for AggregateStopwatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AggregateStopwatch")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a [`Stopwatch`] that will log a message at the given [`log::Level`] if the elapsed time exceeds the threshold.
+/// Useful when the level is only known at runtime.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// let critical = true;
+/// let lvl = if critical { log::Level::Warn } else { log::Level::Debug };
+/// let sw = stopwatch!(lvl, sec!(0), "10 milliseconds of sleep");
+/// std::thread::sleep(millisec!(10));
+/// drop(sw); // Logs: "10 milliseconds of sleep finished in 10ms"
+/// ```
+#[macro_export]
+macro_rules! stopwatch {
+    ($lvl:expr, $threshold:expr, $($arg:tt)+) => {
+        $crate::stopwatch::Stopwatch::new($lvl, $threshold, module_path!(), format_args!($($arg)+))
+    };
+}
+
 /// Creates a [`Stopwatch`] that will log a trace message if the elapsed time exceeds the threshold.
 /// ```
 /// use local_async_utils::prelude::*;
@@ -61,7 +398,7 @@ impl fmt::Debug for Stopwatch {
 #[macro_export]
 macro_rules! trace_stopwatch {
     ($threshold:expr, $($arg:tt)+) => {
-        $crate::stopwatch::Stopwatch::new(log::Level::Trace, $threshold, module_path!(), format_args!($($arg)+))
+        $crate::stopwatch!(log::Level::Trace, $threshold, $($arg)+)
     };
 }
 
@@ -76,7 +413,7 @@ macro_rules! trace_stopwatch {
 #[macro_export]
 macro_rules! debug_stopwatch {
     ($threshold:expr, $($arg:tt)+) => {
-        $crate::stopwatch::Stopwatch::new(log::Level::Debug, $threshold, module_path!(), format_args!($($arg)+))
+        $crate::stopwatch!(log::Level::Debug, $threshold, $($arg)+)
     };
 }
 
@@ -91,7 +428,7 @@ macro_rules! debug_stopwatch {
 #[macro_export]
 macro_rules! info_stopwatch {
     ($threshold:expr, $($arg:tt)+) => {
-        $crate::stopwatch::Stopwatch::new(log::Level::Info, $threshold, module_path!(), format_args!($($arg)+))
+        $crate::stopwatch!(log::Level::Info, $threshold, $($arg)+)
     };
 }
 
@@ -106,7 +443,7 @@ macro_rules! info_stopwatch {
 #[macro_export]
 macro_rules! warn_stopwatch {
     ($threshold:expr, $($arg:tt)+) => {
-        $crate::stopwatch::Stopwatch::new(log::Level::Warn, $threshold, module_path!(), format_args!($($arg)+))
+        $crate::stopwatch!(log::Level::Warn, $threshold, $($arg)+)
     };
 }
 
@@ -121,6 +458,67 @@ macro_rules! warn_stopwatch {
 #[macro_export]
 macro_rules! error_stopwatch {
     ($threshold:expr, $($arg:tt)+) => {
-        $crate::stopwatch::Stopwatch::new(log::Level::Error, $threshold, module_path!(), format_args!($($arg)+))
+        $crate::stopwatch!(log::Level::Error, $threshold, $($arg)+)
+    };
+}
+
+/// Creates a [`ResultStopwatch`] that will log an error message if the operation failed and the elapsed time
+/// exceeds the threshold.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// let sw = error_stopwatch_on_err!(sec!(0), "10 milliseconds of sleep");
+/// std::thread::sleep(millisec!(10));
+/// sw.mark_err();
+/// drop(sw); // Logs: "10 milliseconds of sleep failed in 10ms"
+/// ```
+#[macro_export]
+macro_rules! error_stopwatch_on_err {
+    ($threshold:expr, $($arg:tt)+) => {
+        $crate::stopwatch::ResultStopwatch::new(log::Level::Error, $threshold, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// Measures the execution time of a block using a [`Stopwatch`] at the given level (`trace`, `debug`, `info`,
+/// `warn` or `error`), and evaluates to the value of the block. Logs when the block finishes, if its execution
+/// took longer than the threshold.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// let sum = time_block!(info, sec!(0), "Summation", {
+///     std::thread::sleep(millisec!(10));
+///     (1..=10).sum::<u32>()
+/// }); // Logs: "Summation finished in 10ms"
+/// assert_eq!(sum, 55);
+/// ```
+#[macro_export]
+macro_rules! time_block {
+    (@level trace) => { log::Level::Trace };
+    (@level debug) => { log::Level::Debug };
+    (@level info) => { log::Level::Info };
+    (@level warn) => { log::Level::Warn };
+    (@level error) => { log::Level::Error };
+    ($lvl:ident, $threshold:expr, $label:expr, $body:block) => {{
+        let sw = $crate::stopwatch!($crate::time_block!(@level $lvl), $threshold, "{}", $label);
+        let result = $body;
+        drop(sw);
+        result
+    }};
+}
+
+/// Creates an [`AggregateStopwatch`] that adds the elapsed time to the given [`StopwatchRegistry`] when dropped.
+/// ```
+/// use local_async_utils::prelude::*;
+///
+/// let registry = StopwatchRegistry::new();
+/// let sw = aggregate_stopwatch!(registry, "10 milliseconds of sleep");
+/// std::thread::sleep(millisec!(10));
+/// drop(sw);
+/// assert!(registry.stats("10 milliseconds of sleep").unwrap().min >= millisec!(10));
+/// ```
+#[macro_export]
+macro_rules! aggregate_stopwatch {
+    ($registry:expr, $label:expr) => {
+        $crate::stopwatch::StopwatchRegistry::start(&$registry, $label)
     };
 }
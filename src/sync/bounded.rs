@@ -1,7 +1,8 @@
 use crate::sealed;
-use crate::sync::error::{SendError, TrySendError};
+use crate::sync::error::{SendError, TryRecvError, TrySendError};
 use crate::sync::waker_cell::WakerCell;
 use futures::Stream;
+use futures::stream::FusedStream;
 use std::cell::Cell;
 use std::fmt;
 use std::rc::Rc;
@@ -17,7 +18,31 @@ struct State<T> {
     capacity: usize,
 }
 
-/// Bounded SPSC channel
+impl<T> State<T> {
+    fn has_room(&self) -> bool {
+        self.queue.len() < self.capacity
+    }
+
+    fn pop(&self) -> Option<T> {
+        let item = self.queue.pop()?;
+        self.tx_waker.take_and_wake();
+        Some(item)
+    }
+}
+
+/// Removes the item from the handoff slot of a rendezvous channel if the receiver hasn't taken it,
+/// so that a cancelled `send()` doesn't leave an item behind.
+struct Handoff<'a, T>(&'a State<T>);
+
+impl<T> Drop for Handoff<'_, T> {
+    fn drop(&mut self) {
+        self.0.queue.pop();
+    }
+}
+
+/// Bounded SPSC channel. If `limit` is zero, the channel works in rendezvous mode: [`Sender::send()`] puts
+/// the item into a handoff slot and completes only once the receiver has taken it out in `poll_next()`.
+/// Cancelling the send before that withdraws the item.
 pub fn channel<T>(limit: usize) -> (Sender<T>, Receiver<T>) {
     let shared = Rc::new(State {
         queue: sealed::Queue::with_capacity(limit),
@@ -34,6 +59,9 @@ pub struct Sender<T>(Rc<State<T>>);
 
 impl<T> Sender<T> {
     pub async fn send(&mut self, item: T) -> Result<(), SendError<T>> {
+        if self.0.capacity == 0 {
+            return self.hand_over(item).await;
+        }
         let can_send = poll_fn(|cx| self.poll_ready(cx)).await;
         if can_send {
             self.0.queue.push(item);
@@ -44,14 +72,47 @@ impl<T> Sender<T> {
         }
     }
 
+    async fn hand_over(&mut self, item: T) -> Result<(), SendError<T>> {
+        if !self.0.has_rx.get() {
+            return Err(SendError::Closed(item));
+        }
+        self.0.queue.push(item);
+        self.0.rx_waker.take_and_wake();
+        let slot = Handoff(&self.0);
+        poll_fn(|cx| {
+            if slot.0.queue.is_empty() {
+                Poll::Ready(Ok(()))
+            } else if !slot.0.has_rx.get() {
+                Poll::Ready(Err(SendError::Closed(slot.0.queue.pop().unwrap())))
+            } else {
+                slot.0.tx_waker.update(cx);
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Block the current thread until the item is sent or the receiver is dropped.
+    ///
+    /// Since the channel is not thread-safe, nothing can be received while the thread is blocked.
+    /// This is therefore only a "poll until ready" helper for synchronous code (e.g. FFI callbacks)
+    /// that runs outside of the async runtime, and it will block forever if the channel is full and open.
+    /// Must not be called from within an async context.
+    pub fn blocking_send(&mut self, item: T) -> Result<(), SendError<T>> {
+        futures::executor::block_on(self.send(item))
+    }
+
     pub async fn closed(&mut self) {
         poll_fn(|cx| self.poll_closed(cx)).await
     }
 
+    /// Send an item without waiting. In rendezvous mode an item can't be handed over without waiting
+    /// for the receiver to take it, so this always fails with [`TrySendError::Full`] unless the receiver is gone.
+    #[must_use = "the item may not have been sent"]
     pub fn try_send(&mut self, item: T) -> Result<(), TrySendError<T>> {
         if !self.0.has_rx.get() {
             Err(TrySendError::Closed(item))
-        } else if self.0.queue.len() < self.0.capacity {
+        } else if self.0.has_room() {
             self.0.queue.push(item);
             self.0.rx_waker.take_and_wake();
             Ok(())
@@ -60,6 +121,29 @@ impl<T> Sender<T> {
         }
     }
 
+    /// Send an item without waiting, evicting the oldest item if the channel is full.
+    /// In rendezvous mode there is nothing to evict, so `item` itself is always discarded.
+    /// # Returns
+    /// - `Ok(None)` if the item has been sent without evicting anything.
+    /// - `Ok(Some(_))` with the discarded item if the channel was full.
+    /// - `Err(SendError::Closed(_))` if the receiver has been dropped.
+    #[must_use = "the item may not have been sent"]
+    pub fn send_overwrite(&mut self, item: T) -> Result<Option<T>, SendError<T>> {
+        if !self.0.has_rx.get() {
+            return Err(SendError::Closed(item));
+        }
+        let evicted = if self.0.has_room() {
+            None
+        } else if self.0.capacity == 0 {
+            return Ok(Some(item));
+        } else {
+            self.0.queue.pop()
+        };
+        self.0.queue.push(item);
+        self.0.rx_waker.take_and_wake();
+        Ok(evicted)
+    }
+
     pub fn is_closed(&self) -> bool {
         !self.0.has_rx.get()
     }
@@ -69,6 +153,7 @@ impl<T> Sender<T> {
     }
 
     /// Polls to see if the channel is ready to send a message.
+    /// In rendezvous mode the channel never has room, because [`send()`](Self::send) waits for the handover itself.
     /// # Returns
     /// - `Poll::Ready(true)` if the message can be sent.
     /// - `Poll::Ready(false)` if the receiver has been dropped.
@@ -76,7 +161,7 @@ impl<T> Sender<T> {
     pub fn poll_ready(&mut self, cx: &mut Context) -> Poll<bool> {
         if !self.0.has_rx.get() {
             Poll::Ready(false)
-        } else if self.0.queue.len() < self.0.capacity {
+        } else if self.0.has_room() {
             Poll::Ready(true)
         } else {
             self.0.tx_waker.update(cx);
@@ -110,6 +195,7 @@ impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Sender")
             .field("channel_len", &self.0.queue.len())
+            .field("capacity", &self.0.capacity)
             .field("has_receiver", &self.0.has_rx.get())
             .finish_non_exhaustive()
     }
@@ -125,14 +211,81 @@ impl<T> Receiver<T> {
     pub fn queue(&self) -> &sealed::Queue<T> {
         &self.0.queue
     }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        if let Some(item) = self.0.pop() {
+            Ok(item)
+        } else if !self.0.has_tx.get() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Convert into a plain [`Stream`](Stream). The receiver is always [`Unpin`], so the returned stream
+    /// can be fed into combinators that require `Unpin` without any pinning.
+    pub fn into_stream(self) -> impl Stream<Item = T> + Unpin {
+        self
+    }
+
+    /// Convert into a stream that yields the received items transformed by `f`.
+    /// ```
+    /// # use local_async_utils::prelude::*;
+    /// # use futures::StreamExt;
+    /// # futures::executor::block_on(async {
+    /// let (mut sender, receiver) = local_bounded::channel(1);
+    /// let mut lengths = receiver.map(|s: &str| s.len());
+    /// sender.try_send("hello").unwrap();
+    /// assert_eq!(lengths.next().await, Some(5));
+    /// # });
+    /// ```
+    pub fn map<U, F>(self, f: F) -> futures::stream::Map<Self, F>
+    where
+        F: FnMut(T) -> U,
+    {
+        futures::StreamExt::map(self, f)
+    }
+
+    /// Convert into a stream that yields only the received items for which `pred` returns `true`.
+    /// Unlike [`StreamExt::filter()`](futures::StreamExt::filter), `pred` returns a plain `bool` rather than a future.
+    pub fn filter_sync<F>(self, mut pred: F) -> impl futures::Stream<Item = T> + Unpin
+    where
+        F: FnMut(&T) -> bool,
+    {
+        futures::StreamExt::filter(self, move |item| std::future::ready(pred(item)))
+    }
+
+    /// Polls to see if an item is available, without removing it from the channel.
+    /// In rendezvous mode peeking doesn't complete the pending send, only taking the item out does.
+    /// # Returns
+    /// - `Poll::Ready(Some(()))` if the next call to `poll_next()` will yield an item.
+    /// - `Poll::Ready(None)` if the sender has been dropped and the channel is empty.
+    /// - `Poll::Pending` if the channel is empty.
+    pub fn poll_peek(&mut self, cx: &mut Context) -> Poll<Option<()>> {
+        if !self.0.queue.is_empty() {
+            Poll::Ready(Some(()))
+        } else if !self.0.has_tx.get() {
+            Poll::Ready(None)
+        } else {
+            self.0.rx_waker.update(cx);
+            Poll::Pending
+        }
+    }
+
+    /// Inspect the next item without removing it from the channel.
+    pub fn peek_map<R, F>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.0.queue.front_map(f)
+    }
 }
 
 impl<T> Stream for Receiver<T> {
     type Item = T;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        if let Some(item) = self.0.queue.pop() {
-            self.0.tx_waker.take_and_wake();
+        if let Some(item) = self.0.pop() {
             Poll::Ready(Some(item))
         } else if !self.0.has_tx.get() {
             Poll::Ready(None)
@@ -141,8 +294,26 @@ impl<T> Stream for Receiver<T> {
             Poll::Pending
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.queue.len();
+        if self.is_closed() {
+            (len, Some(len))
+        } else {
+            (len, None)
+        }
+    }
 }
 
+impl<T> FusedStream for Receiver<T> {
+    fn is_terminated(&self) -> bool {
+        self.is_closed() && self.0.queue.is_empty()
+    }
+}
+
+// Only holds an `Rc`, so it is `Unpin` regardless of `T`.
+impl<T> Unpin for Receiver<T> {}
+
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         self.0.has_rx.set(false);
@@ -154,6 +325,7 @@ impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Receiver")
             .field("channel_len", &self.0.queue.len())
+            .field("capacity", &self.0.capacity)
             .field("has_sender", &self.0.has_tx.get())
             .finish_non_exhaustive()
     }
@@ -162,7 +334,8 @@ impl<T> fmt::Debug for Receiver<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use static_assertions::assert_not_impl_any;
+    use futures::StreamExt;
+    use static_assertions::{assert_impl_all, assert_not_impl_any};
     use std::sync::Arc;
     use tokio_test::task::spawn;
     use tokio_test::{assert_pending, assert_ready};
@@ -172,9 +345,26 @@ mod tests {
         assert_not_impl_any!(Arc<Sender<usize>>: std::marker::Send, Sync);
         assert_not_impl_any!(Arc<Receiver<usize>>: std::marker::Send, Sync);
         assert_not_impl_any!(Sender<usize>: std::marker::Send, Sync, Clone);
+        assert_impl_all!(Receiver<std::marker::PhantomPinned>: Unpin);
         assert_not_impl_any!(Receiver<usize>: std::marker::Send, Sync, Clone);
     }
 
+    #[test]
+    fn test_debug_doesnt_require_debug_items() {
+        struct Opaque;
+
+        let (mut sender, receiver) = channel(2);
+        sender.try_send(Opaque).unwrap();
+        assert_eq!(
+            format!("{sender:?}"),
+            "Sender { channel_len: 1, capacity: 2, has_receiver: true, .. }"
+        );
+        assert_eq!(
+            format!("{receiver:?}"),
+            "Receiver { channel_len: 1, capacity: 2, has_sender: true, .. }"
+        );
+    }
+
     #[test]
     fn test_sender_notifies_receiver() {
         let (mut sender, receiver) = channel::<i32>(2);
@@ -225,4 +415,225 @@ mod tests {
         drop(send);
         assert!(sender.is_closed());
     }
+
+    #[test]
+    fn test_receiver_is_terminated() {
+        let (mut sender, receiver) = channel::<i32>(2);
+        let mut receiver = spawn(receiver);
+        assert!(!receiver.is_terminated());
+
+        sender.try_send(42).unwrap();
+        drop(sender);
+        assert!(!receiver.is_terminated());
+
+        assert_eq!(Some(42), assert_ready!(receiver.poll_next()));
+        assert!(receiver.is_terminated());
+    }
+
+    #[test]
+    fn test_receiver_size_hint() {
+        let (mut sender, receiver) = channel::<i32>(2);
+        assert_eq!(receiver.size_hint(), (0, None));
+
+        sender.try_send(1).unwrap();
+        assert_eq!(receiver.size_hint(), (1, None));
+
+        drop(sender);
+        assert_eq!(receiver.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn test_receiver_peek_map() {
+        let (mut sender, receiver) = channel::<i32>(2);
+        assert_eq!(receiver.peek_map(|i| *i), None);
+
+        sender.try_send(1).unwrap();
+        sender.try_send(2).unwrap();
+        assert_eq!(receiver.peek_map(|i| *i), Some(1));
+
+        let mut receiver = spawn(receiver);
+        assert_eq!(Some(1), assert_ready!(receiver.poll_next()));
+        assert_eq!(receiver.peek_map(|i| *i), Some(2));
+    }
+
+    #[test]
+    fn test_blocking_send() {
+        let (mut sender, receiver) = channel::<i32>(1);
+        assert_eq!(sender.blocking_send(1), Ok(()));
+        assert_eq!(receiver.queue().len(), 1);
+
+        drop(receiver);
+        assert_eq!(sender.blocking_send(2), Err(SendError::Closed(2)));
+    }
+
+    #[test]
+    fn test_into_stream_is_unpin() {
+        let (mut sender, receiver) = channel::<std::marker::PhantomPinned>(1);
+        let mut stream = receiver.into_stream();
+        let mut next = spawn(stream.next());
+        assert_pending!(next.poll());
+
+        sender.try_send(std::marker::PhantomPinned).unwrap();
+        assert!(next.is_woken());
+        assert!(assert_ready!(next.poll()).is_some());
+    }
+
+    #[test]
+    fn test_receiver_poll_peek() {
+        let (mut sender, mut receiver) = channel::<i32>(2);
+
+        let mut peek = spawn(poll_fn(|cx| receiver.poll_peek(cx)));
+        assert_pending!(peek.poll());
+
+        sender.try_send(1).unwrap();
+        assert!(peek.is_woken());
+        assert_eq!(assert_ready!(peek.poll()), Some(()));
+        assert_eq!(assert_ready!(peek.poll()), Some(()));
+        drop(peek);
+
+        assert_eq!(receiver.try_recv(), Ok(1));
+        drop(sender);
+        assert_eq!(assert_ready!(spawn(poll_fn(|cx| receiver.poll_peek(cx))).poll()), None);
+    }
+
+    #[test]
+    fn test_rendezvous_send_waits_for_receiver() {
+        let (mut sender, mut receiver) = channel(0);
+
+        let mut send = spawn(sender.send(42));
+        assert_pending!(send.poll());
+        assert!(!send.is_woken());
+        assert_pending!(send.poll());
+
+        let mut recv = spawn(receiver.next());
+        assert_eq!(assert_ready!(recv.poll()), Some(42));
+        assert!(send.is_woken());
+        assert_ready!(send.poll()).unwrap();
+    }
+
+    #[test]
+    fn test_rendezvous_try_send() {
+        let (mut sender, mut receiver) = channel(0);
+        assert_eq!(sender.try_send(1), Err(TrySendError::Full(1)));
+
+        let mut recv = spawn(receiver.next());
+        assert_pending!(recv.poll());
+        assert_eq!(sender.try_send(2), Err(TrySendError::Full(2)));
+        assert!(!recv.is_woken());
+        drop(recv);
+
+        drop(receiver);
+        assert_eq!(sender.try_send(3), Err(TrySendError::Closed(3)));
+    }
+
+    #[test]
+    fn test_rendezvous_dropped_receive_blocks_sender() {
+        let (mut sender, mut receiver) = channel(0);
+
+        let mut recv = spawn(StreamExt::next(&mut receiver));
+        assert_pending!(recv.poll());
+        drop(recv);
+
+        let mut send = spawn(sender.send(1));
+        assert_pending!(send.poll());
+
+        let mut peek = spawn(poll_fn(|cx| receiver.poll_peek(cx)));
+        assert_eq!(assert_ready!(peek.poll()), Some(()));
+        drop(peek);
+        assert_pending!(send.poll());
+
+        let mut recv = spawn(receiver.next());
+        assert_eq!(assert_ready!(recv.poll()), Some(1));
+        assert!(send.is_woken());
+        assert_ready!(send.poll()).unwrap();
+    }
+
+    #[test]
+    fn test_rendezvous_cancelled_send_withdraws_item() {
+        let (mut sender, mut receiver) = channel(0);
+
+        let mut send = spawn(sender.send(1));
+        assert_pending!(send.poll());
+        assert_eq!(receiver.queue().len(), 1);
+        drop(send);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+        let mut send = spawn(sender.send(2));
+        assert_pending!(send.poll());
+        drop(receiver);
+        assert!(send.is_woken());
+        assert_eq!(assert_ready!(send.poll()), Err(SendError::Closed(2)));
+    }
+
+    #[test]
+    fn test_send_overwrite_evicts_oldest() {
+        let (mut sender, mut receiver) = channel::<i32>(3);
+        let mut recv = spawn(receiver.next());
+        assert_pending!(recv.poll());
+
+        assert_eq!(sender.send_overwrite(1), Ok(None));
+        assert!(recv.is_woken());
+        assert_eq!(assert_ready!(recv.poll()), Some(1));
+        drop(recv);
+
+        for i in 2..=4 {
+            assert_eq!(sender.send_overwrite(i), Ok(None));
+        }
+        assert_eq!(sender.send_overwrite(5), Ok(Some(2)));
+        assert_eq!(sender.send_overwrite(6), Ok(Some(3)));
+        assert_eq!(receiver.queue().len(), 3);
+
+        assert_eq!(receiver.try_recv(), Ok(4));
+        assert_eq!(receiver.try_recv(), Ok(5));
+        assert_eq!(receiver.try_recv(), Ok(6));
+
+        drop(receiver);
+        assert_eq!(sender.send_overwrite(7), Err(SendError::Closed(7)));
+    }
+
+    #[test]
+    fn test_rendezvous_send_overwrite() {
+        let (mut sender, mut receiver) = channel(0);
+        assert_eq!(sender.send_overwrite(1), Ok(Some(1)));
+
+        let mut recv = spawn(receiver.next());
+        assert_pending!(recv.poll());
+        assert_eq!(sender.send_overwrite(2), Ok(Some(2)));
+        assert!(!recv.is_woken());
+    }
+
+    #[test]
+    fn test_receiver_map_and_filter() {
+        let (mut sender, receiver) = channel::<i32>(4);
+        let mut stream = spawn(receiver.filter_sync(|n| n % 2 == 0).map(|n| n * 10));
+        assert_pending!(stream.poll_next());
+
+        for i in 1..=4 {
+            sender.try_send(i).unwrap();
+        }
+        assert!(stream.is_woken());
+        assert_eq!(assert_ready!(stream.poll_next()), Some(20));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(40));
+        drop(sender);
+        assert_eq!(assert_ready!(stream.poll_next()), None);
+    }
+
+    #[test]
+    fn test_try_recv() {
+        let (mut sender, mut receiver) = channel::<i32>(1);
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+        sender.try_send(1).unwrap();
+        let mut send = spawn(sender.send(2));
+        assert_pending!(send.poll());
+
+        assert_eq!(receiver.try_recv(), Ok(1));
+        assert!(send.is_woken());
+        assert_eq!(Ok(()), assert_ready!(send.poll()));
+        drop(send);
+        drop(sender);
+
+        assert_eq!(receiver.try_recv(), Ok(2));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    }
 }
@@ -0,0 +1,288 @@
+use crate::sync::error::{RecvError, SendError};
+use crate::sync::waker_cell::{ReceiverWakers, WakerCell};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+struct State<T> {
+    buffer: RefCell<VecDeque<T>>,
+    capacity: usize,
+    /// Position of the oldest item in `buffer` since the creation of the channel
+    head_pos: Cell<u64>,
+    has_sender: Cell<bool>,
+    receiver_count: Cell<usize>,
+    rx_wakers: ReceiverWakers,
+}
+
+impl<T> State<T> {
+    fn register_receiver(self: &Rc<Self>, next_pos: u64) -> Receiver<T> {
+        let waker = self.rx_wakers.register();
+        self.receiver_count.update(|count| count + 1);
+        Receiver {
+            state: self.clone(),
+            waker,
+            next_pos,
+        }
+    }
+
+    fn tail_pos(&self) -> u64 {
+        self.head_pos.get() + self.buffer.borrow().len() as u64
+    }
+
+    fn notify_all(&self) {
+        self.rx_wakers.wake_all();
+    }
+}
+
+/// Single-producer multi-consumer channel where each receiver sees every sent value.
+/// Retains up to `capacity` most recent values, receivers that fall further behind will miss the oldest ones.
+/// Non-thread-safe equivalent of [`tokio::sync::broadcast`](https://docs.rs/tokio/latest/tokio/sync/broadcast/index.html).
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be non-zero");
+    let state = Rc::new(State {
+        buffer: RefCell::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        head_pos: Cell::new(0),
+        has_sender: Cell::new(true),
+        receiver_count: Cell::new(0),
+        rx_wakers: Default::default(),
+    });
+    let receiver = state.register_receiver(0);
+    (Sender(state), receiver)
+}
+
+pub struct Sender<T>(Rc<State<T>>);
+
+impl<T> Sender<T> {
+    /// Append a value to the channel, evicting the oldest one if the channel is at capacity.
+    /// Fails if there are no receivers.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            return Err(SendError::Closed(value));
+        }
+        let evicted = {
+            let mut buffer = self.0.buffer.borrow_mut();
+            buffer.push_back(value);
+            if buffer.len() > self.0.capacity {
+                self.0.head_pos.update(|pos| pos + 1);
+                buffer.pop_front()
+            } else {
+                None
+            }
+        };
+        // drop the evicted value outside of the borrow
+        drop(evicted);
+        self.0.notify_all();
+        Ok(())
+    }
+
+    /// Create a new receiver that will see all values sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.0.register_receiver(self.0.tail_pos())
+    }
+
+    pub fn receiver_count(&self) -> usize {
+        self.0.receiver_count.get()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.receiver_count.get() == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.0.has_sender.set(false);
+        self.0.notify_all();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("channel_len", &self.0.buffer.borrow().len())
+            .field("receiver_count", &self.0.receiver_count.get())
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Receiver<T> {
+    state: Rc<State<T>>,
+    waker: Rc<WakerCell>,
+    next_pos: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Wait for the next value that hasn't been seen by this receiver.
+    /// # Returns
+    /// - `Ok(value)` with a clone of the next value.
+    /// - `Err(RecvError::Lagged(n))` if this receiver has missed `n` values that were evicted from the channel.
+    ///   The next call will return the oldest value that is still retained.
+    /// - `Err(RecvError::Closed)` if the sender has been dropped and all values have been seen.
+    pub fn recv(&mut self) -> impl Future<Output = Result<T, RecvError>> + '_ {
+        poll_fn(|cx| self.poll_recv(cx))
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        let head_pos = self.state.head_pos.get();
+        if self.next_pos < head_pos {
+            let missed = head_pos - self.next_pos;
+            self.next_pos = head_pos;
+            return Poll::Ready(Err(RecvError::Lagged(missed)));
+        }
+        let index = (self.next_pos - head_pos) as usize;
+        if let Some(value) = self.state.buffer.borrow().get(index) {
+            self.next_pos += 1;
+            Poll::Ready(Ok(value.clone()))
+        } else if !self.state.has_sender.get() {
+            Poll::Ready(Err(RecvError::Closed))
+        } else {
+            self.waker.update(cx);
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Number of values that haven't been seen by this receiver yet, including the evicted ones.
+    pub fn len(&self) -> usize {
+        (self.state.tail_pos() - self.next_pos) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_closed(&self) -> bool {
+        !self.state.has_sender.get()
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    /// The new receiver starts at the same position as `self`.
+    fn clone(&self) -> Self {
+        self.state.register_receiver(self.next_pos)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.state.receiver_count.update(|count| count - 1);
+        self.state.rx_wakers.unregister(&self.waker);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("unseen", &self.len())
+            .field("has_sender", &self.state.has_sender.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use std::sync::Arc;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_channel_static_properties() {
+        assert_not_impl_any!(Arc<Sender<usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Arc<Receiver<usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Sender<usize>: std::marker::Send, Sync, Clone);
+        assert_not_impl_any!(Receiver<usize>: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_each_receiver_sees_every_value() {
+        let (sender, mut receiver1) = channel(4);
+        let mut receiver2 = receiver1.clone();
+
+        let mut recv1 = spawn(receiver1.recv());
+        let mut recv2 = spawn(receiver2.recv());
+        assert_pending!(recv1.poll());
+        assert_pending!(recv2.poll());
+
+        sender.send(1).unwrap();
+        assert!(recv1.is_woken());
+        assert!(recv2.is_woken());
+        assert_eq!(assert_ready!(recv1.poll()), Ok(1));
+        assert_eq!(assert_ready!(recv2.poll()), Ok(1));
+        drop((recv1, recv2));
+
+        sender.send(2).unwrap();
+        sender.send(3).unwrap();
+        assert_eq!(receiver1.len(), 2);
+        assert_eq!(assert_ready!(spawn(receiver1.recv()).poll()), Ok(2));
+        assert_eq!(assert_ready!(spawn(receiver1.recv()).poll()), Ok(3));
+        assert_pending!(spawn(receiver1.recv()).poll());
+        assert_eq!(assert_ready!(spawn(receiver2.recv()).poll()), Ok(2));
+        assert_eq!(receiver2.len(), 1);
+    }
+
+    #[test]
+    fn test_slow_receiver_lags() {
+        let (sender, mut receiver) = channel(2);
+        for i in 0..5 {
+            sender.send(i).unwrap();
+        }
+        assert_eq!(receiver.len(), 5);
+        assert_eq!(assert_ready!(spawn(receiver.recv()).poll()), Err(RecvError::Lagged(3)));
+        assert_eq!(assert_ready!(spawn(receiver.recv()).poll()), Ok(3));
+        assert_eq!(assert_ready!(spawn(receiver.recv()).poll()), Ok(4));
+        assert!(receiver.is_empty());
+    }
+
+    #[test]
+    fn test_subscriber_sees_only_new_values() {
+        let (sender, _receiver) = channel(2);
+        sender.send("a").unwrap();
+
+        let mut subscriber = sender.subscribe();
+        assert!(subscriber.is_empty());
+        assert_pending!(spawn(subscriber.recv()).poll());
+
+        sender.send("b").unwrap();
+        assert_eq!(assert_ready!(spawn(subscriber.recv()).poll()), Ok("b"));
+    }
+
+    #[test]
+    fn test_sender_dropped() {
+        let (sender, mut receiver) = channel(2);
+        sender.send(1).unwrap();
+
+        let mut recv = spawn(receiver.recv());
+        assert_eq!(assert_ready!(recv.poll()), Ok(1));
+        assert_pending!(recv.poll());
+
+        drop(sender);
+        assert!(recv.is_woken());
+        assert_eq!(assert_ready!(recv.poll()), Err(RecvError::Closed));
+        drop(recv);
+        assert!(receiver.is_closed());
+    }
+
+    #[test]
+    fn test_receivers_dropped() {
+        let (sender, receiver) = channel(1);
+        let receiver2 = receiver.clone();
+        drop(receiver);
+        assert!(!sender.is_closed());
+        assert_eq!(sender.receiver_count(), 1);
+
+        drop(receiver2);
+        assert!(sender.is_closed());
+        assert_eq!(sender.0.rx_wakers.len(), 0);
+        assert_eq!(sender.send(1), Err(SendError::Closed(1)));
+    }
+}
@@ -5,6 +5,8 @@ use std::fmt;
 use std::future::{Future, poll_fn};
 use std::ops::ControlFlow;
 use std::rc::Rc;
+#[cfg(feature = "tokio")]
+use std::time::Duration;
 
 struct Data {
     notified: Cell<bool>,
@@ -70,6 +72,17 @@ impl Receiver {
     pub fn wait_for_one(&mut self) -> impl Future<Output = bool> + '_ {
         poll_fn(|cx| self.0.poll_wait(cx)).map(|v| v.is_some())
     }
+
+    /// Same as [`wait_for_one()`](Receiver::wait_for_one) but gives up after `duration`.
+    /// # Returns
+    /// - `Some(true)` if signalled.
+    /// - `Some(false)` if the sender has been dropped.
+    /// - `None` if timed out.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn wait_for_one_timeout(&mut self, duration: Duration) -> Option<bool> {
+        crate::time::timeout(duration, self.wait_for_one()).await.ok()
+    }
 }
 
 impl Drop for Receiver {
@@ -88,3 +101,34 @@ impl fmt::Debug for Receiver {
             .finish()
     }
 }
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::sec;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_for_one_timeout() {
+        let (sender, mut receiver) = condvar();
+
+        let mut wait = spawn(receiver.wait_for_one_timeout(sec!(1)));
+        assert_pending!(wait.poll());
+        tokio::time::advance(sec!(1)).await;
+        assert!(wait.is_woken());
+        assert_eq!(assert_ready!(wait.poll()), None);
+        drop(wait);
+
+        let mut wait = spawn(receiver.wait_for_one_timeout(sec!(1)));
+        assert_pending!(wait.poll());
+        sender.signal_one();
+        assert!(wait.is_woken());
+        assert_eq!(assert_ready!(wait.poll()), Some(true));
+        drop(wait);
+
+        drop(sender);
+        let mut wait = spawn(receiver.wait_for_one_timeout(sec!(1)));
+        assert_eq!(assert_ready!(wait.poll()), Some(false));
+    }
+}
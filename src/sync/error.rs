@@ -11,6 +11,23 @@ pub enum SendError<T> {
     Closed(T),
 }
 
+impl<T> TrySendError<T> {
+    /// Get back the value that failed to be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Closed(value) => value,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TrySendError::Closed(_))
+    }
+}
+
 impl<T> fmt::Debug for TrySendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -41,6 +58,15 @@ impl<T> From<TrySendError<T>> for io::Error {
     }
 }
 
+impl<T> SendError<T> {
+    /// Get back the value that failed to be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Closed(value) => value,
+        }
+    }
+}
+
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -67,3 +93,123 @@ impl<T> From<SendError<T>> for io::Error {
         }
     }
 }
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+impl fmt::Debug for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("TryRecvError::Empty"),
+            TryRecvError::Disconnected => f.write_str("TryRecvError::Disconnected"),
+        }
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("channel is empty"),
+            TryRecvError::Disconnected => f.write_str("channel is closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+impl From<TryRecvError> for io::Error {
+    fn from(err: TryRecvError) -> Self {
+        let source = format!("{err}");
+        match err {
+            TryRecvError::Empty => io::Error::new(io::ErrorKind::WouldBlock, source),
+            TryRecvError::Disconnected => io::Error::new(io::ErrorKind::BrokenPipe, source),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// The receiver has fallen behind and missed this many values.
+    Lagged(u64),
+    Closed,
+}
+
+impl fmt::Debug for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "RecvError::Lagged({n})"),
+            RecvError::Closed => f.write_str("RecvError::Closed"),
+        }
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "receiver lagged behind by {n} values"),
+            RecvError::Closed => f.write_str("channel is closed"),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+impl From<RecvError> for io::Error {
+    fn from(err: RecvError) -> Self {
+        let source = format!("{err}");
+        match err {
+            RecvError::Lagged(_) => io::Error::other(source),
+            RecvError::Closed => io::Error::new(io::ErrorKind::BrokenPipe, source),
+        }
+    }
+}
+
+/// Error indicating that the channel has been closed and no more items will be received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+
+impl fmt::Display for Closed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel is closed")
+    }
+}
+
+impl std::error::Error for Closed {}
+
+impl From<Closed> for io::Error {
+    fn from(err: Closed) -> Self {
+        io::Error::new(io::ErrorKind::BrokenPipe, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_value_from_error() {
+        let err = TrySendError::Full(1);
+        assert!(err.is_full());
+        assert!(!err.is_closed());
+        assert_eq!(err.into_inner(), 1);
+
+        let err = TrySendError::Closed(2);
+        assert!(!err.is_full());
+        assert!(err.is_closed());
+        assert_eq!(err.into_inner(), 2);
+
+        assert_eq!(SendError::Closed(3).into_inner(), 3);
+    }
+
+    #[test]
+    fn test_try_recv_error_into_io_error() {
+        let err: io::Error = TryRecvError::Empty.into();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        let err: io::Error = TryRecvError::Disconnected.into();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
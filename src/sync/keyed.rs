@@ -0,0 +1,256 @@
+use super::shared_state::{SharedState, Source};
+use crate::sync::error::TrySendError;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Insertion-ordered map: `order` maps sequence numbers to keys, and `values` maps keys to their
+/// sequence number and value, so that both replacing and popping take logarithmic time.
+struct Entries<K, V> {
+    order: BTreeMap<u64, K>,
+    values: HashMap<K, (u64, V)>,
+    next_seq: u64,
+    limit: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> Entries<K, V> {
+    fn try_insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        if self.values.len() >= self.limit && !self.values.contains_key(&key) {
+            return Err((key, value));
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if let Some((old_seq, _)) = self.values.insert(key.clone(), (seq, value)) {
+            self.order.remove(&old_seq);
+        }
+        self.order.insert(seq, key);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<(K, V)> {
+        let (_, key) = self.order.pop_first()?;
+        let (_, value) = self.values.remove(&key)?;
+        Some((key, value))
+    }
+}
+
+impl<K, V> Entries<K, V> {
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+}
+
+struct Data<K, V> {
+    entries: RefCell<Entries<K, V>>,
+    sender_count: Cell<usize>,
+    has_receiver: Cell<bool>,
+}
+
+impl<K: Hash + Eq + Clone, V> Source for Data<K, V> {
+    type Item = (K, V);
+
+    fn try_yield_one(&self) -> ControlFlow<Option<Self::Item>> {
+        if let Some(entry) = self.entries.borrow_mut().pop() {
+            ControlFlow::Break(Some(entry))
+        } else if self.sender_count.get() == 0 {
+            ControlFlow::Break(None)
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+type StateRc<K, V> = Rc<SharedState<Data<K, V>>>;
+
+pub struct Sender<K, V>(StateRc<K, V>);
+
+pub struct Receiver<K, V>(StateRc<K, V>);
+
+/// Bounded MPSC channel that keeps only the latest value for each key. Sending a value with
+/// a key that is already queued replaces the old value and moves the entry to the back of the queue,
+/// so the number of queued entries never exceeds the number of distinct keys. At most `limit` distinct
+/// keys can be queued at once; replacing the value of a queued key always succeeds.
+///
+/// # Panics
+/// If `limit` is zero.
+pub fn channel<K: Hash + Eq + Clone, V>(limit: usize) -> (Sender<K, V>, Receiver<K, V>) {
+    assert!(limit > 0, "limit must be greater than zero");
+    let state = SharedState::new(Data {
+        entries: RefCell::new(Entries {
+            order: Default::default(),
+            values: Default::default(),
+            next_seq: 0,
+            limit,
+        }),
+        sender_count: Cell::new(1),
+        has_receiver: Cell::new(true),
+    });
+    (Sender(state.clone()), Receiver(state))
+}
+
+impl<K: Hash + Eq + Clone, V> Sender<K, V> {
+    pub fn is_closed(&self) -> bool {
+        !self.0.has_receiver.get()
+    }
+
+    /// Queue `value` for `key`, replacing any value that is already queued for it.
+    /// # Returns
+    /// - `Ok(())` if the value has been queued.
+    /// - `Err(TrySendError::Full(_))` if `key` is not queued yet and the channel already holds `limit` keys.
+    /// - `Err(TrySendError::Closed(_))` if the receiver has been dropped.
+    #[must_use = "the value may not have been sent"]
+    pub fn try_send(&self, key: K, value: V) -> Result<(), TrySendError<(K, V)>> {
+        if self.is_closed() {
+            return Err(TrySendError::Closed((key, value)));
+        }
+        self.0.entries.borrow_mut().try_insert(key, value).map_err(TrySendError::Full)?;
+        self.0.notify();
+        Ok(())
+    }
+}
+
+impl<K, V> Drop for Sender<K, V> {
+    fn drop(&mut self) {
+        let prev_count = self.0.sender_count.get();
+        self.0.sender_count.set(prev_count - 1);
+        self.0.notify();
+    }
+}
+
+impl<K, V> Clone for Sender<K, V> {
+    fn clone(&self) -> Self {
+        let prev_count = self.0.sender_count.get();
+        self.0.sender_count.set(prev_count + 1);
+        Self(self.0.clone())
+    }
+}
+
+impl<K, V> fmt::Debug for Sender<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("channel_len", &self.0.entries.borrow().len())
+            .field("has_receiver", &self.0.has_receiver.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Receiver<K, V> {
+    pub fn is_closed(&self) -> bool {
+        self.0.sender_count.get() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> futures::Stream for Receiver<K, V> {
+    type Item = (K, V);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().0.poll_wait(cx)
+    }
+}
+
+impl<K, V> Drop for Receiver<K, V> {
+    fn drop(&mut self) {
+        self.0.receiver_dropped();
+        self.0.has_receiver.set(false);
+    }
+}
+
+impl<K, V> fmt::Debug for Receiver<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("channel_len", &self.len())
+            .field("sender_count", &self.0.sender_count.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::*;
+    use std::sync::Arc;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_channel_static_properties() {
+        assert_not_impl_any!(Arc<Sender<usize, usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Arc<Receiver<usize, usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Sender<usize, usize>: std::marker::Send, Sync);
+        assert_not_impl_any!(Receiver<usize, usize>: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_sender_notifies_receiver() {
+        let (sender, receiver) = channel::<&str, i32>(8);
+
+        let mut receiver = spawn(receiver);
+        assert_pending!(receiver.poll_next());
+
+        sender.try_send("a", 42).unwrap();
+        assert!(receiver.is_woken());
+        assert_eq!(Some(("a", 42)), assert_ready!(receiver.poll_next()));
+        assert_pending!(receiver.poll_next());
+
+        drop(sender);
+        assert!(receiver.is_woken());
+        assert_eq!(None, assert_ready!(receiver.poll_next()));
+    }
+
+    #[test]
+    fn test_latest_value_wins() {
+        let (sender, receiver) = channel::<&str, i32>(8);
+
+        sender.try_send("a", 1).unwrap();
+        sender.try_send("b", 2).unwrap();
+        sender.try_send("c", 3).unwrap();
+        sender.try_send("a", 4).unwrap();
+        assert_eq!(receiver.len(), 3);
+        drop(sender);
+
+        let mut receiver = spawn(receiver);
+        assert_eq!(Some(("b", 2)), assert_ready!(receiver.poll_next()));
+        assert_eq!(Some(("c", 3)), assert_ready!(receiver.poll_next()));
+        assert_eq!(Some(("a", 4)), assert_ready!(receiver.poll_next()));
+        assert_eq!(None, assert_ready!(receiver.poll_next()));
+    }
+
+    #[test]
+    fn test_limit_applies_to_new_keys_only() {
+        let (sender, receiver) = channel::<&str, i32>(2);
+
+        sender.try_send("a", 1).unwrap();
+        sender.try_send("b", 2).unwrap();
+        assert_eq!(sender.try_send("c", 3), Err(TrySendError::Full(("c", 3))));
+        sender.try_send("a", 4).unwrap();
+        assert_eq!(receiver.len(), 2);
+
+        let mut receiver = spawn(receiver);
+        assert_eq!(Some(("b", 2)), assert_ready!(receiver.poll_next()));
+        sender.try_send("c", 3).unwrap();
+        assert_eq!(Some(("a", 4)), assert_ready!(receiver.poll_next()));
+        assert_eq!(Some(("c", 3)), assert_ready!(receiver.poll_next()));
+        assert_pending!(receiver.poll_next());
+    }
+
+    #[test]
+    fn test_send_after_receiver_dropped() {
+        let (sender, receiver) = channel::<&str, i32>(8);
+        drop(receiver);
+        assert!(sender.is_closed());
+        assert_eq!(sender.try_send("a", 1), Err(TrySendError::Closed(("a", 1))));
+    }
+}
@@ -0,0 +1,142 @@
+//! Combinators for consuming several channels at once.
+
+use crate::sync::unbounded::Receiver;
+use futures::Stream;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Merge several receivers into one stream that takes items from them in rotating order,
+/// so that a busy receiver can't starve the others. Closed receivers are dropped,
+/// and the stream ends when all of them are closed and drained.
+/// Single-threaded analogue of [`futures::stream::select_all`] with fairness guarantees.
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use futures::StreamExt;
+/// # futures::executor::block_on(async {
+/// let (high_tx, high_rx) = local_unbounded::channel();
+/// let (low_tx, low_rx) = local_unbounded::channel();
+/// high_tx.send(1).unwrap();
+/// high_tx.send(2).unwrap();
+/// low_tx.send(10).unwrap();
+/// drop((high_tx, low_tx));
+///
+/// let items: Vec<_> = local_merge::round_robin(vec![high_rx, low_rx]).collect().await;
+/// assert_eq!(items, [1, 10, 2]);
+/// # });
+/// ```
+pub fn round_robin<T>(receivers: Vec<Receiver<T>>) -> RoundRobin<T> {
+    RoundRobin { receivers, next: 0 }
+}
+
+/// Stream returned from [`round_robin()`].
+pub struct RoundRobin<T> {
+    receivers: Vec<Receiver<T>>,
+    next: usize,
+}
+
+impl<T> RoundRobin<T> {
+    /// Number of receivers that haven't been closed and drained yet.
+    pub fn len(&self) -> usize {
+        self.receivers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receivers.is_empty()
+    }
+}
+
+impl<T> Stream for RoundRobin<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut index = this.next;
+        let mut unpolled = this.receivers.len();
+        while unpolled > 0 {
+            if index >= this.receivers.len() {
+                index = 0;
+            }
+            match Pin::new(&mut this.receivers[index]).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.next = index + 1;
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => {
+                    this.receivers.remove(index);
+                }
+                Poll::Pending => index += 1,
+            }
+            unpolled -= 1;
+        }
+        this.next = index;
+        if this.receivers.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> fmt::Debug for RoundRobin<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoundRobin")
+            .field("receivers", &self.receivers)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::unbounded::channel;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_round_robin_is_fair() {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        let (tx3, rx3) = channel();
+        for i in 0..3 {
+            tx1.send(10 + i).unwrap();
+        }
+        tx3.send(30).unwrap();
+
+        let mut stream = spawn(round_robin(vec![rx1, rx2, rx3]));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(10));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(30));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(11));
+
+        tx2.send(20).unwrap();
+        assert_eq!(assert_ready!(stream.poll_next()), Some(20));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(12));
+        assert_pending!(stream.poll_next());
+
+        tx3.send(31).unwrap();
+        assert!(stream.is_woken());
+        assert_eq!(assert_ready!(stream.poll_next()), Some(31));
+    }
+
+    #[test]
+    fn test_round_robin_drops_closed_receivers() {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel::<i32>();
+
+        let mut stream = spawn(round_robin(vec![rx1, rx2]));
+        assert_pending!(stream.poll_next());
+
+        tx1.send(1).unwrap();
+        drop(tx1);
+        assert!(stream.is_woken());
+        assert_eq!(assert_ready!(stream.poll_next()), Some(1));
+        assert_pending!(stream.poll_next());
+        assert_eq!(stream.len(), 1);
+
+        drop(tx2);
+        assert!(stream.is_woken());
+        assert_eq!(assert_ready!(stream.poll_next()), None);
+        assert!(stream.is_empty());
+    }
+}
@@ -1,13 +1,22 @@
 //! Synchronization primitives for single-threaded async programming.
 
 pub mod bounded;
+pub mod broadcast;
 pub mod condvar;
 pub mod error;
+pub mod keyed;
+pub mod merge;
+pub mod mutex;
+pub mod notify;
 pub mod oneshot;
 #[cfg(feature = "tokio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
 pub mod pipe;
+pub mod primitive;
+pub mod rwlock;
+pub mod select;
 pub mod semaphore;
 mod shared_state;
 pub mod unbounded;
 mod waker_cell;
+pub mod watch;
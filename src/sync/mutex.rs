@@ -0,0 +1,226 @@
+use crate::sealed;
+use crate::sync::waker_cell::Waiter;
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Asynchronous mutex for single-threaded use. Waiting tasks acquire the lock in FIFO order.
+pub struct Mutex<T> {
+    locked: Cell<bool>,
+    waiters: sealed::Queue<Rc<Waiter>>,
+    value: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            locked: Cell::new(false),
+            waiters: sealed::Queue::new(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        Lock {
+            mutex: self,
+            waiter: None,
+        }
+        .await
+    }
+
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        if self.locked.replace(true) {
+            None
+        } else {
+            Some(MutexGuard(self))
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    fn unlock(&self) {
+        // hand the lock directly over to the next waiter, if any
+        if let Some(waiter) = self.waiters.pop() {
+            waiter.granted.set(true);
+            waiter.waker.take_and_wake();
+        } else {
+            self.locked.set(false);
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex")
+            .field("locked", &self.locked.get())
+            .field("waiters", &self.waiters.len())
+            .finish_non_exhaustive()
+    }
+}
+
+struct Lock<'a, T> {
+    mutex: &'a Mutex<T>,
+    waiter: Option<Rc<Waiter>>,
+}
+
+impl<'a, T> Future for Lock<'a, T> {
+    type Output = MutexGuard<'a, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match &this.waiter {
+            None if !this.mutex.locked.replace(true) => Poll::Ready(MutexGuard(this.mutex)),
+            None => {
+                let waiter = Rc::new(Waiter::default());
+                waiter.waker.update(cx);
+                this.mutex.waiters.push(waiter.clone());
+                this.waiter = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.granted.get() => {
+                this.waiter = None;
+                Poll::Ready(MutexGuard(this.mutex))
+            }
+            Some(waiter) => {
+                waiter.waker.update(cx);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Lock<'_, T> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            if waiter.granted.get() {
+                self.mutex.unlock();
+            } else {
+                self.mutex.waiters.remove_first(|w| Rc::ptr_eq(w, &waiter));
+            }
+        }
+    }
+}
+
+/// RAII guard providing access to the data protected by a [`Mutex`]. Unlocks the mutex when dropped.
+pub struct MutexGuard<'a, T>(&'a Mutex<T>);
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the guard guarantees exclusive access
+        unsafe { &*self.0.value.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the guard guarantees exclusive access
+        unsafe { &mut *self.0.value.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.unlock();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MutexGuard").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_mutex_static_properties() {
+        assert_not_impl_any!(Mutex<usize>: Sync);
+        assert_not_impl_any!(MutexGuard<'static, usize>: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_lock_and_try_lock() {
+        let mutex = Mutex::new(1);
+
+        let mut guard = assert_ready!(spawn(mutex.lock()).poll());
+        *guard += 1;
+        assert!(mutex.try_lock().is_none());
+        assert_pending!(spawn(mutex.lock()).poll());
+
+        drop(guard);
+        let guard = mutex.try_lock().unwrap();
+        assert_eq!(*guard, 2);
+        drop(guard);
+
+        assert_eq!(mutex.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_waiters_acquire_in_fifo_order() {
+        let mutex = Mutex::new(Vec::new());
+        let guard = mutex.try_lock().unwrap();
+
+        let mut lock1 = spawn(mutex.lock());
+        let mut lock2 = spawn(mutex.lock());
+        assert_pending!(lock2.poll());
+        assert_pending!(lock1.poll());
+
+        drop(guard);
+        assert!(lock2.is_woken());
+        assert!(!lock1.is_woken());
+        assert!(mutex.try_lock().is_none());
+
+        assert_pending!(lock1.poll());
+        let mut guard = assert_ready!(lock2.poll());
+        guard.push(2);
+        drop(guard);
+
+        assert!(lock1.is_woken());
+        let mut guard = assert_ready!(lock1.poll());
+        guard.push(1);
+        drop(guard);
+        drop((lock1, lock2));
+
+        assert_eq!(mutex.into_inner(), [2, 1]);
+    }
+
+    #[test]
+    fn test_dropped_waiter_passes_lock_on() {
+        let mutex = Mutex::new(());
+        let guard = mutex.try_lock().unwrap();
+
+        let mut lock1 = spawn(mutex.lock());
+        let mut lock2 = spawn(mutex.lock());
+        let mut lock3 = spawn(mutex.lock());
+        assert_pending!(lock1.poll());
+        assert_pending!(lock2.poll());
+        assert_pending!(lock3.poll());
+
+        drop(lock2);
+        drop(guard);
+        assert!(lock1.is_woken());
+
+        drop(lock1);
+        assert!(lock3.is_woken());
+        let _guard = assert_ready!(lock3.poll());
+    }
+}
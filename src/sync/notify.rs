@@ -0,0 +1,133 @@
+use super::shared_state::{SharedState, Source};
+use std::cell::Cell;
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::ops::ControlFlow;
+use std::rc::Rc;
+use std::task::Poll;
+
+struct Data {
+    permit: Cell<bool>,
+    generation: Cell<usize>,
+}
+
+impl Source for Data {
+    type Item = ();
+
+    fn try_yield_one(&self) -> ControlFlow<Option<Self::Item>> {
+        if self.permit.replace(false) {
+            ControlFlow::Break(Some(()))
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// Notifies a single task to wake up. Non-thread-safe equivalent of
+/// [`tokio::sync::Notify`](https://docs.rs/tokio/latest/tokio/sync/struct.Notify.html).
+/// Only one task can wait for notifications at a time.
+#[derive(Clone)]
+pub struct Notify(Rc<SharedState<Data>>);
+
+impl Notify {
+    pub fn new() -> Self {
+        Self(SharedState::new(Data {
+            permit: Cell::new(false),
+            generation: Cell::new(0),
+        }))
+    }
+
+    /// Wait for a notification. Completes immediately if a permit has been stored by
+    /// [`notify_one()`](Self::notify_one), consuming the permit.
+    pub fn notified(&self) -> impl Future<Output = ()> + '_ {
+        let generation = self.0.generation.get();
+        let mut state = self.0.clone();
+        poll_fn(move |cx| {
+            if state.generation.get() != generation {
+                Poll::Ready(())
+            } else {
+                state.poll_wait(cx).map(|_| ())
+            }
+        })
+    }
+
+    /// Wake the waiting task, or store a permit for the next call to [`notified()`](Self::notified)
+    /// if no one is waiting. At most one permit is stored.
+    pub fn notify_one(&self) {
+        self.0.permit.set(true);
+        self.0.notify();
+    }
+
+    /// Wake the task waiting on a [`notified()`](Self::notified) future created before this call.
+    /// Doesn't store a permit.
+    pub fn notify_waiters(&self) {
+        self.0.generation.update(|g| g.wrapping_add(1));
+        self.0.notify();
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify").field("permit", &self.0.permit.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_notify_static_properties() {
+        assert_not_impl_any!(Notify: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_notify_one_wakes_waiter() {
+        let notify = Notify::new();
+
+        let mut notified = spawn(notify.notified());
+        assert_pending!(notified.poll());
+
+        notify.notify_one();
+        assert!(notified.is_woken());
+        assert_ready!(notified.poll());
+        drop(notified);
+
+        assert_pending!(spawn(notify.notified()).poll());
+    }
+
+    #[test]
+    fn test_notify_one_stores_single_permit() {
+        let notify = Notify::new();
+        notify.notify_one();
+        notify.notify_one();
+
+        assert_ready!(spawn(notify.notified()).poll());
+        assert_pending!(spawn(notify.notified()).poll());
+    }
+
+    #[test]
+    fn test_notify_waiters_does_not_store_permit() {
+        let notify = Notify::new();
+
+        let mut notified = spawn(notify.notified());
+        assert_pending!(notified.poll());
+
+        notify.notify_waiters();
+        assert!(notified.is_woken());
+        assert_ready!(notified.poll());
+        drop(notified);
+
+        notify.notify_waiters();
+        assert_pending!(spawn(notify.notified()).poll());
+    }
+}
@@ -1,5 +1,6 @@
 use super::shared_state::{SharedState, Source};
 use crate::sync::error::SendError;
+use futures::FutureExt;
 use std::cell::Cell;
 use std::fmt;
 use std::future::Future;
@@ -42,6 +43,7 @@ pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
 }
 
 impl<T> Sender<T> {
+    #[must_use = "the item may not have been sent"]
     pub fn send(self, value: T) -> Result<(), SendError<T>> {
         if self.0.has_receiver.get() {
             self.0.value.set(Some(value));
@@ -68,6 +70,46 @@ impl<T> fmt::Debug for Sender<T> {
     }
 }
 
+impl<T> Receiver<T> {
+    /// Apply `f` to the received value. Resolves to `None` if the sender was dropped without sending.
+    /// Not named `map()` so as not to shadow [`FutureExt::map()`], whose closure gets the `Option<T>` instead.
+    /// ```
+    /// # use local_async_utils::prelude::*;
+    /// # futures::executor::block_on(async {
+    /// let (sender, receiver) = local_oneshot::channel::<i32>();
+    /// sender.send(21).unwrap();
+    /// assert_eq!(receiver.map_value(|v| v * 2).await, Some(42));
+    /// # });
+    /// ```
+    pub fn map_value<U, F>(self, f: F) -> impl Future<Output = Option<U>>
+    where
+        F: FnOnce(T) -> U,
+    {
+        FutureExt::map(self, |value| value.map(f))
+    }
+
+    /// Invoke `f` on the received value before passing it on. `f` is not invoked if the sender was dropped
+    /// without sending. Not named `inspect()` so as not to shadow [`FutureExt::inspect()`].
+    /// ```
+    /// # use local_async_utils::prelude::*;
+    /// # futures::executor::block_on(async {
+    /// let (sender, receiver) = local_oneshot::channel::<i32>();
+    /// drop(sender);
+    /// assert_eq!(receiver.inspect_value(|_| unreachable!()).await, None);
+    /// # });
+    /// ```
+    pub fn inspect_value<F>(self, f: F) -> impl Future<Output = Option<T>>
+    where
+        F: FnOnce(&T),
+    {
+        FutureExt::inspect(self, |value| {
+            if let Some(value) = value {
+                f(value)
+            }
+        })
+    }
+}
+
 impl<T> Future for Receiver<T> {
     type Output = Option<T>;
 
@@ -1,11 +1,13 @@
 use crate::shared::UnsafeShared;
+use futures::{Sink, Stream};
 use std::cell::UnsafeCell;
+use std::future::poll_fn;
 use std::io::BufRead;
 use std::rc::Rc;
-use std::task::{Context, Poll, Waker};
-use std::{cmp, fmt, io};
+use std::task::{Context, Poll, Waker, ready};
+use std::{cmp, fmt, io, mem};
 use std::{collections::VecDeque, pin::Pin};
-use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
 
 /// Unidirectional in-memory pipe implementing `AsyncRead` and `AsyncWrite`.
 /// A more efficient version of [`tokio::io::SimplexStream`](https://docs.rs/tokio/latest/tokio/io/struct.SimplexStream.html)
@@ -13,27 +15,85 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 pub struct Pipe {
     buffer: VecDeque<u8>,
     is_closed: bool,
+    /// The reader is gone, as opposed to `is_closed` which is also set when the writer shuts down.
+    is_read_closed: bool,
+    write_error: Option<io::ErrorKind>,
     max_buf_size: usize,
+    wake_writer_below: usize,
+    total_written: u64,
     read_waker: Option<Waker>,
-    write_waker: Option<Waker>,
+    write_wakers: Vec<Waker>,
+    writer_count: usize,
 }
 
 impl Pipe {
     /// Create a new `Pipe` with a fixed-size pre-allocated buffer of `max_buf_size` bytes.
     pub fn new(max_buf_size: usize) -> Self {
+        Self::with_watermark(max_buf_size, max_buf_size)
+    }
+
+    /// Create a new `Pipe` like [`Pipe::new()`], except that a blocked writer is only woken once the number
+    /// of buffered bytes drops below `watermark`. This reduces the number of wakeups when the reader consumes
+    /// data in small portions.
+    ///
+    /// # Panics
+    /// If `watermark` is zero or greater than `max_buf_size`.
+    pub fn with_watermark(max_buf_size: usize, watermark: usize) -> Self {
+        assert!(
+            watermark > 0 && watermark <= max_buf_size,
+            "watermark must be within 1..={max_buf_size}"
+        );
         Self {
             buffer: VecDeque::with_capacity(max_buf_size),
             is_closed: false,
+            is_read_closed: false,
+            write_error: None,
             max_buf_size,
+            wake_writer_below: watermark,
+            total_written: 0,
             read_waker: None,
-            write_waker: None,
+            write_wakers: Vec::new(),
+            writer_count: 0,
         }
     }
 
+    /// Discard all buffered data, keeping the allocated capacity. Wakes all blocked writers, if any.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.wake_writers();
+    }
+
+    /// Restore the pipe to its initial state so that it can be reused even after having been closed.
+    /// Any buffered data is discarded, and registered wakers are dropped without being woken.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.total_written = 0;
+        self.is_closed = false;
+        self.is_read_closed = false;
+        self.write_error = None;
+        self.read_waker = None;
+        self.write_wakers.clear();
+    }
+
     /// Split the pipe into non-[`Send`] owned readable and writable ends.
-    pub fn into_split(self) -> (ReadEnd, WriteEnd) {
+    pub fn into_split(mut self) -> (ReadEnd, WriteEnd) {
+        self.writer_count = 1;
         let pipe = Rc::new(UnsafeCell::new(self));
-        (ReadEnd(pipe.clone()), WriteEnd(pipe))
+        (ReadEnd(pipe.clone(), VecDeque::new()), WriteEnd(pipe, false))
+    }
+
+    /// Close the pipe so that the reader gets an error of the given `kind` instead of EOF once it has read
+    /// all buffered data. Subsequent writes fail with `ErrorKind::BrokenPipe`.
+    pub fn close_write_with_error(&mut self, kind: io::ErrorKind) {
+        self.write_error.get_or_insert(kind);
+        self.close_write();
+    }
+
+    /// Split the pipe into type-erased readable and writable ends, e.g. for storing them along with other
+    /// I/O objects in a collection.
+    pub fn into_boxed_split(self) -> (BoxedReadEnd, BoxedWriteEnd) {
+        let (read_end, write_end) = self.into_split();
+        (Box::pin(read_end), Box::pin(write_end))
     }
 
     fn close_write(&mut self) {
@@ -45,8 +105,29 @@ impl Pipe {
 
     fn close_read(&mut self) {
         self.is_closed = true;
-        if let Some(waker) = self.write_waker.take() {
-            waker.wake();
+        self.is_read_closed = true;
+        self.wake_writers();
+    }
+
+    /// Number of bytes read or discarded since creation or the last reset.
+    fn total_consumed(&self) -> u64 {
+        self.total_written - self.buffer.len() as u64
+    }
+
+    fn wake_writer_if_below_watermark(&mut self) {
+        if self.buffer.len() < self.wake_writer_below {
+            self.wake_writers();
+        }
+    }
+
+    fn wake_writers(&mut self) {
+        self.write_wakers.drain(..).for_each(Waker::wake);
+    }
+
+    /// Several writers may be blocked at once if the write end has been cloned.
+    fn park_writer(&mut self, cx: &mut Context) {
+        if !self.write_wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            self.write_wakers.push(cx.waker().clone());
         }
     }
 
@@ -60,19 +141,48 @@ impl Pipe {
             let bytes_copied = copy_slice(buf, head) + copy_slice(buf, tail);
             if bytes_copied > 0 {
                 self.buffer.consume(bytes_copied);
-                if let Some(waker) = self.write_waker.take() {
-                    waker.wake();
-                }
+                self.wake_writer_if_below_watermark();
             }
             Poll::Ready(Ok(()))
         } else if self.is_closed {
-            Poll::Ready(Ok(()))
+            Poll::Ready(self.write_error.map_or(Ok(()), |kind| Err(kind.into())))
         } else {
             self.read_waker = Some(cx.waker().clone());
             Poll::Pending
         }
     }
 
+    fn poll_chunk_internal(&mut self, cx: &mut Context) -> Poll<Option<io::Result<Vec<u8>>>> {
+        if !self.buffer.is_empty() {
+            let chunk = self.buffer.drain(..).collect();
+            self.wake_writer_if_below_watermark();
+            Poll::Ready(Some(Ok(chunk)))
+        } else if self.is_closed {
+            Poll::Ready(self.write_error.map(|kind| Err(kind.into())))
+        } else {
+            self.read_waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn poll_fill_buf_internal(&mut self, cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        if !self.buffer.is_empty() {
+            Poll::Ready(Ok(self.buffer.as_slices().0))
+        } else if self.is_closed {
+            Poll::Ready(self.write_error.map_or(Ok(&[]), |kind| Err(kind.into())))
+        } else {
+            self.read_waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn consume_internal(&mut self, amt: usize) {
+        if amt > 0 {
+            self.buffer.consume(amt);
+            self.wake_writer_if_below_watermark();
+        }
+    }
+
     fn poll_write_internal(
         mut self: Pin<&mut Self>,
         cx: &mut Context,
@@ -81,20 +191,60 @@ impl Pipe {
         if self.is_closed {
             return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
         }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
         let available = self.max_buf_size - self.buffer.len();
         if available == 0 {
-            self.write_waker = Some(cx.waker().clone());
+            self.park_writer(cx);
             return Poll::Pending;
         }
 
         let bytes_to_copy = cmp::min(buf.len(), available);
         self.buffer.extend(&buf[..bytes_to_copy]);
+        self.total_written += bytes_to_copy as u64;
         if let Some(waker) = self.read_waker.take() {
             waker.wake();
         }
         Poll::Ready(Ok(bytes_to_copy))
     }
 
+    fn poll_write_atomic_internal(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<()>> {
+        if self.is_closed {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if buf.len() > self.max_buf_size {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer exceeds pipe capacity",
+            )));
+        }
+        if buf.len() > self.max_buf_size - self.buffer.len() {
+            self.park_writer(cx);
+            return Poll::Pending;
+        }
+
+        self.buffer.extend(buf);
+        self.total_written += buf.len() as u64;
+        if !buf.is_empty()
+            && let Some(waker) = self.read_waker.take()
+        {
+            waker.wake();
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_flush_drained_internal(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        if self.buffer.is_empty() {
+            Poll::Ready(Ok(()))
+        } else if self.is_read_closed {
+            Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()))
+        } else {
+            self.park_writer(cx);
+            Poll::Pending
+        }
+    }
+
     fn poll_write_vectored_internal(
         mut self: Pin<&mut Self>,
         cx: &mut Context,
@@ -103,9 +253,12 @@ impl Pipe {
         if self.is_closed {
             return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
         }
+        if bufs.iter().all(|buf| buf.is_empty()) {
+            return Poll::Ready(Ok(0));
+        }
         let available = self.max_buf_size - self.buffer.len();
         if available == 0 {
-            self.write_waker = Some(cx.waker().clone());
+            self.park_writer(cx);
             return Poll::Pending;
         }
 
@@ -120,6 +273,7 @@ impl Pipe {
             remaining -= len;
         }
 
+        self.total_written += (available - remaining) as u64;
         if let Some(waker) = self.read_waker.take() {
             waker.wake();
         }
@@ -127,6 +281,15 @@ impl Pipe {
     }
 }
 
+/// Pipes can't seek, so only `SeekFrom::Current(0)` is accepted, to query the current position.
+fn start_seek_internal(position: io::SeekFrom) -> io::Result<()> {
+    if position == io::SeekFrom::Current(0) {
+        Ok(())
+    } else {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
 fn copy_slice(dest: &mut ReadBuf, src: &[u8]) -> usize {
     let bytes_to_copy = cmp::min(dest.remaining(), src.len());
     if bytes_to_copy != 0 {
@@ -145,6 +308,16 @@ impl AsyncRead for Pipe {
     }
 }
 
+impl AsyncBufRead for Pipe {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().poll_fill_buf_internal(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().consume_internal(amt)
+    }
+}
+
 impl AsyncWrite for Pipe {
     fn poll_write(
         self: Pin<&mut Self>,
@@ -179,29 +352,121 @@ impl AsyncWrite for Pipe {
     }
 }
 
+/// Only supports querying the position, which is the number of bytes consumed by the reader so far.
+impl AsyncSeek for Pipe {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        start_seek_internal(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.total_consumed()))
+    }
+}
+
 impl fmt::Debug for Pipe {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Pipe")
             .field("pending_bytes", &self.buffer.len())
             .field("is_closed", &self.is_closed)
+            .field("write_error", &self.write_error)
             .finish_non_exhaustive()
     }
 }
 
+/// Type-erased readable end of a [`Pipe`], see [`Pipe::into_boxed_split()`]. Not thread-safe.
+pub type BoxedReadEnd = Pin<Box<dyn AsyncRead>>;
+
+/// Type-erased writable end of a [`Pipe`], see [`Pipe::into_boxed_split()`]. Not thread-safe.
+pub type BoxedWriteEnd = Pin<Box<dyn AsyncWrite>>;
+
 /// The readable end of a [`Pipe`]. Not thread-safe.
-pub struct ReadEnd(Rc<UnsafeCell<Pipe>>);
+///
+/// The second field holds a copy of the bytes at the front of the pipe that have been returned from
+/// `poll_fill_buf()` but not consumed yet, so that the returned slice never aliases the pipe's buffer
+/// while the writer is modifying it.
+pub struct ReadEnd(Rc<UnsafeCell<Pipe>>, VecDeque<u8>);
 
 /// The writable end of a [`Pipe`]. Not thread-safe.
-pub struct WriteEnd(Rc<UnsafeCell<Pipe>>);
+///
+/// The second field is set once this handle has been shut down, so that it stops counting as a writer
+/// while other clones may keep writing.
+pub struct WriteEnd(Rc<UnsafeCell<Pipe>>, bool);
+
+impl ReadEnd {
+    /// Number of bytes currently buffered in the pipe and available for reading without blocking.
+    pub fn buffered(&self) -> usize {
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { self.0.clone().with_unchecked(|pipe| pipe.buffer.len()) }
+    }
+
+    /// Convert into a stream that yields all currently buffered bytes whenever they become available,
+    /// and ends when the writer is dropped or shut down.
+    pub fn into_chunks(mut self) -> impl Stream<Item = io::Result<Vec<u8>>> {
+        futures::stream::poll_fn(move |cx| {
+            // SAFETY: exclusive access is guaranteed by the single-threaded context
+            unsafe { self.0.with_unchecked(|pipe| pipe.poll_chunk_internal(cx)) }
+        })
+    }
+
+    /// Convert into a stream of messages written by [`WriteEnd::into_length_delimited()`].
+    /// Yields an `ErrorKind::UnexpectedEof` error if the writer is closed in the middle of a message.
+    pub fn into_length_delimited(self) -> impl Stream<Item = io::Result<Vec<u8>>> + Unpin {
+        LengthDelimitedReader {
+            inner: self,
+            header: [0; FRAME_HEADER_LEN],
+            header_filled: 0,
+            payload: Vec::new(),
+        }
+    }
+}
 
 impl AsyncRead for ReadEnd {
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
+        let ReadEnd(pipe, filled) = self.get_mut();
+        // the copied bytes are still at the front of the pipe, and are about to be read from there
+        filled.clear();
         // SAFETY: exclusive access is guaranteed by the single-threaded context
-        unsafe { self.0.with_unchecked(|pipe| Pin::new(pipe).poll_read(cx, buf)) }
+        unsafe { pipe.with_unchecked(|pipe| Pin::new(pipe).poll_read(cx, buf)) }
+    }
+}
+
+impl AsyncBufRead for ReadEnd {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let ReadEnd(pipe, filled) = self.get_mut();
+        if filled.is_empty() {
+            // SAFETY: exclusive access is guaranteed by the single-threaded context, and the pipe's
+            // buffer is copied before the closure returns
+            ready!(unsafe {
+                pipe.with_unchecked(|pipe| {
+                    Pin::new(pipe).poll_fill_buf(cx).map_ok(|data| filled.extend(data))
+                })
+            })?;
+        }
+        Poll::Ready(Ok(filled.as_slices().0))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let ReadEnd(pipe, filled) = self.get_mut();
+        filled.consume(cmp::min(amt, filled.len()));
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { pipe.with_unchecked(|pipe| Pin::new(pipe).consume(amt)) }
+    }
+}
+
+/// Only supports querying the position, which is the number of bytes consumed by the reader so far.
+impl AsyncSeek for ReadEnd {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        start_seek_internal(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        // SAFETY: single-threaded context, and the reference doesn't outlive this call
+        let pipe = unsafe { &*self.0.get() };
+        Poll::Ready(Ok(pipe.total_consumed()))
     }
 }
 
@@ -219,14 +484,108 @@ impl fmt::Debug for ReadEnd {
     }
 }
 
+impl WriteEnd {
+    fn capacity(&self) -> usize {
+        // SAFETY: single-threaded context, and the reference doesn't outlive this call
+        let pipe = unsafe { &*self.0.get() };
+        pipe.max_buf_size
+    }
+
+    /// Number of bytes that can be written to the pipe before a write will block.
+    pub fn remaining(&self) -> usize {
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { self.0.clone().with_unchecked(|pipe| pipe.max_buf_size - pipe.buffer.len()) }
+    }
+
+    /// Stop counting this handle as a writer. The pipe is closed for writing once no writers are left.
+    fn release(&mut self) {
+        if !mem::replace(&mut self.1, true) {
+            // SAFETY: exclusive access is guaranteed by the single-threaded context
+            unsafe {
+                self.0.with_unchecked(|pipe| {
+                    pipe.writer_count -= 1;
+                    if pipe.writer_count == 0 {
+                        pipe.close_write();
+                    }
+                })
+            }
+        }
+    }
+
+    /// Run a write operation on the pipe, failing with `ErrorKind::BrokenPipe` if this handle has been shut down.
+    fn with_writable<R>(
+        &mut self,
+        f: impl FnOnce(&mut Pipe) -> Poll<io::Result<R>>,
+    ) -> Poll<io::Result<R>> {
+        if self.1 {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { self.0.with_unchecked(f) }
+    }
+
+    /// Close the pipe abnormally: once the reader has read all buffered data, it will get an error
+    /// of the given `kind` instead of EOF.
+    pub fn abort(mut self, kind: io::ErrorKind) {
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { self.0.with_unchecked(|pipe| pipe.close_write_with_error(kind)) }
+    }
+
+    /// Writes the whole `buf` in one go, so that the reader never observes a partial write.
+    /// # Returns
+    /// - `Poll::Ready(Ok(()))` if `buf` has been written in its entirety.
+    /// - `Poll::Ready(Err(_))` if the pipe is closed, or if `buf` is larger than the pipe capacity
+    ///   (`ErrorKind::InvalidInput`).
+    /// - `Poll::Pending` if there is not enough room for the whole `buf` yet.
+    pub fn poll_write_atomic(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<()>> {
+        self.with_writable(|pipe| pipe.poll_write_atomic_internal(cx, buf))
+    }
+
+    /// Wait until there is room for the whole `buf` and write it.
+    /// See [`poll_write_atomic()`](Self::poll_write_atomic).
+    pub async fn write_atomic(&mut self, buf: &[u8]) -> io::Result<()> {
+        poll_fn(|cx| self.poll_write_atomic(cx, buf)).await
+    }
+
+    /// Polls until all written data has been consumed by the reader. Unlike `poll_flush()`,
+    /// which always completes immediately.
+    /// # Returns
+    /// - `Poll::Ready(Ok(()))` if the buffer is empty.
+    /// - `Poll::Ready(Err(_))` if the reader has been dropped before the buffer was drained.
+    /// - `Poll::Pending` if there is still unread data in the buffer.
+    pub fn poll_flush_drained(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { self.0.with_unchecked(|pipe| pipe.poll_flush_drained_internal(cx)) }
+    }
+
+    /// Convert into a sink of messages, each written as a 4-byte big-endian length followed by the payload.
+    /// The messages can be decoded using [`ReadEnd::into_length_delimited()`].
+    ///
+    /// A message whose frame fits into the pipe is written in one go (see [`write_atomic()`](Self::write_atomic)),
+    /// so sinks created from several clones of the write end never interleave such messages. Larger messages
+    /// are written in parts, which is only safe if this is the only writer.
+    pub fn into_length_delimited(self) -> impl Sink<Vec<u8>, Error = io::Error> + Unpin {
+        LengthDelimitedWriter {
+            inner: self,
+            frame: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Wait until all written data has been consumed by the reader.
+    /// See [`poll_flush_drained()`](Self::poll_flush_drained).
+    pub async fn flush_drained(&mut self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_flush_drained(cx)).await
+    }
+}
+
 impl AsyncWrite for WriteEnd {
     fn poll_write(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<Result<usize, io::Error>> {
-        // SAFETY: exclusive access is guaranteed by the single-threaded context
-        unsafe { self.0.with_unchecked(|pipe| Pin::new(pipe).poll_write(cx, buf)) }
+        self.with_writable(|pipe| Pin::new(pipe).poll_write(cx, buf))
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
@@ -234,12 +593,14 @@ impl AsyncWrite for WriteEnd {
         unsafe { self.0.with_unchecked(|pipe| Pin::new(pipe).poll_flush(cx)) }
     }
 
+    /// Only closes the pipe once no other clones of this write end are left.
+    /// Further writes using this handle fail with `ErrorKind::BrokenPipe`.
     fn poll_shutdown(
         mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
+        _cx: &mut Context<'_>,
     ) -> Poll<Result<(), io::Error>> {
-        // SAFETY: exclusive access is guaranteed by the single-threaded context
-        unsafe { self.0.with_unchecked(|pipe| Pin::new(pipe).poll_shutdown(cx)) }
+        self.release();
+        Poll::Ready(Ok(()))
     }
 
     fn poll_write_vectored(
@@ -247,8 +608,7 @@ impl AsyncWrite for WriteEnd {
         cx: &mut Context<'_>,
         bufs: &[io::IoSlice<'_>],
     ) -> Poll<Result<usize, io::Error>> {
-        // SAFETY: exclusive access is guaranteed by the single-threaded context
-        unsafe { self.0.with_unchecked(|pipe| Pin::new(pipe).poll_write_vectored(cx, bufs)) }
+        self.with_writable(|pipe| Pin::new(pipe).poll_write_vectored(cx, bufs))
     }
 
     fn is_write_vectored(&self) -> bool {
@@ -256,10 +616,34 @@ impl AsyncWrite for WriteEnd {
     }
 }
 
+/// Only supports querying the position, which is the number of bytes written so far.
+impl AsyncSeek for WriteEnd {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        start_seek_internal(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        // SAFETY: single-threaded context, and the reference doesn't outlive this call
+        let pipe = unsafe { &*self.0.get() };
+        Poll::Ready(Ok(pipe.total_written))
+    }
+}
+
+/// All clones write to the same pipe. The reader gets EOF once all of them have been dropped or shut down.
+/// Concurrent writes from several clones may be interleaved, unless they are done using
+/// [`write_atomic()`](WriteEnd::write_atomic) or [`into_length_delimited()`](WriteEnd::into_length_delimited).
+impl Clone for WriteEnd {
+    fn clone(&self) -> Self {
+        let mut pipe = self.0.clone();
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { pipe.with_unchecked(|pipe| pipe.writer_count += 1) };
+        Self(pipe, false)
+    }
+}
+
 impl Drop for WriteEnd {
     fn drop(&mut self) {
-        // SAFETY: exclusive access is guaranteed by the single-threaded context
-        unsafe { self.0.with_unchecked(|pipe| pipe.close_write()) }
+        self.release();
     }
 }
 
@@ -270,6 +654,165 @@ impl fmt::Debug for WriteEnd {
     }
 }
 
+/// Wrap `read_end` so that it reports EOF after `limit` bytes have been read, leaving any further data in the pipe.
+/// Useful for reading length-prefixed messages or bodies with a known content length.
+pub fn take(read_end: ReadEnd, limit: u64) -> LimitedReadEnd {
+    LimitedReadEnd {
+        inner: read_end,
+        remaining: limit,
+    }
+}
+
+/// The readable end of a [`Pipe`] that delivers at most a fixed number of bytes, see [`take()`]. Not thread-safe.
+pub struct LimitedReadEnd {
+    inner: ReadEnd,
+    remaining: u64,
+}
+
+impl LimitedReadEnd {
+    /// Number of bytes that can still be read before reaching the limit.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Get back the underlying [`ReadEnd`], regardless of whether the limit has been reached.
+    pub fn into_inner(self) -> ReadEnd {
+        self.inner
+    }
+}
+
+impl AsyncRead for LimitedReadEnd {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.remaining == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let max = cmp::min(buf.remaining() as u64, this.remaining) as usize;
+        let mut limited_buf = buf.take(max);
+        ready!(Pin::new(&mut this.inner).poll_read(cx, &mut limited_buf))?;
+        let bytes_read = limited_buf.filled().len();
+        // SAFETY: `limited_buf` points into the unfilled part of `buf`, and `bytes_read` bytes have been initialized
+        unsafe { buf.assume_init(bytes_read) };
+        buf.advance(bytes_read);
+        this.remaining -= bytes_read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for LimitedReadEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LimitedReadEnd")
+            .field("inner", &self.inner)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+const FRAME_HEADER_LEN: usize = 4;
+
+struct LengthDelimitedWriter {
+    inner: WriteEnd,
+    frame: Vec<u8>,
+    written: usize,
+}
+
+impl LengthDelimitedWriter {
+    fn poll_write_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.written == 0 && !self.frame.is_empty() && self.frame.len() <= self.inner.capacity()
+        {
+            ready!(self.inner.poll_write_atomic(cx, &self.frame))?;
+            self.written = self.frame.len();
+        }
+        while self.written < self.frame.len() {
+            let bytes_written =
+                ready!(Pin::new(&mut self.inner).poll_write(cx, &self.frame[self.written..]))?;
+            self.written += bytes_written;
+        }
+        self.frame.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Sink<Vec<u8>> for LengthDelimitedWriter {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_write_frame(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Vec<u8>) -> io::Result<()> {
+        let len = u32::try_from(item.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+        let this = self.get_mut();
+        debug_assert!(this.frame.is_empty(), "start_send() called without poll_ready()");
+        this.frame.reserve(FRAME_HEADER_LEN + item.len());
+        this.frame.extend_from_slice(&len.to_be_bytes());
+        this.frame.extend_from_slice(&item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_write_frame(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_frame(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+struct LengthDelimitedReader {
+    inner: ReadEnd,
+    header: [u8; FRAME_HEADER_LEN],
+    header_filled: usize,
+    payload: Vec<u8>,
+}
+
+impl Stream for LengthDelimitedReader {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let available = ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+            if available.is_empty() {
+                if this.header_filled == 0 {
+                    return Poll::Ready(None);
+                }
+                this.header_filled = 0;
+                this.payload.clear();
+                return Poll::Ready(Some(Err(io::ErrorKind::UnexpectedEof.into())));
+            }
+            let consumed = if this.header_filled < FRAME_HEADER_LEN {
+                let len = cmp::min(available.len(), FRAME_HEADER_LEN - this.header_filled);
+                this.header[this.header_filled..this.header_filled + len]
+                    .copy_from_slice(&available[..len]);
+                this.header_filled += len;
+                len
+            } else {
+                let payload_len = u32::from_be_bytes(this.header) as usize;
+                let len = cmp::min(available.len(), payload_len - this.payload.len());
+                this.payload.extend_from_slice(&available[..len]);
+                len
+            };
+            Pin::new(&mut this.inner).consume(consumed);
+
+            if this.header_filled == FRAME_HEADER_LEN
+                && this.payload.len() == u32::from_be_bytes(this.header) as usize
+            {
+                this.header_filled = 0;
+                return Poll::Ready(Some(Ok(mem::take(&mut this.payload))));
+            }
+        }
+    }
+}
+
 /// Create a bi-directional in-memory stream of bytes using two [`Pipe`]s in opposite directions.
 /// Non-thread-safe equivalent of [`tokio::io::duplex`](https://docs.rs/tokio/latest/tokio/io/fn.duplex.html).
 /// # Returns
@@ -297,6 +840,14 @@ impl DuplexEnd {
         let DuplexEnd(read, write) = self;
         (read, write)
     }
+
+    /// Signal that no more data will be read from this end, causing subsequent writes
+    /// from the other end to fail with [`io::ErrorKind::BrokenPipe`]. Doesn't affect writing.
+    pub fn shutdown_read(&mut self) {
+        let DuplexEnd(read, _write) = self;
+        // SAFETY: exclusive access is guaranteed by the single-threaded context
+        unsafe { read.0.with_unchecked(|pipe| pipe.close_read()) }
+    }
 }
 
 impl AsyncRead for DuplexEnd {
@@ -310,6 +861,18 @@ impl AsyncRead for DuplexEnd {
     }
 }
 
+impl AsyncBufRead for DuplexEnd {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let DuplexEnd(read, _write) = self.get_mut();
+        Pin::new(read).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let DuplexEnd(read, _write) = self.get_mut();
+        Pin::new(read).consume(amt)
+    }
+}
+
 impl AsyncWrite for DuplexEnd {
     fn poll_write(
         self: Pin<&mut Self>,
@@ -353,7 +916,7 @@ impl fmt::Debug for DuplexEnd {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
     use tokio_test::{assert_pending, assert_ready, task::spawn};
 
     #[test]
@@ -401,6 +964,51 @@ mod tests {
         assert_eq!(&buf[..], data);
     }
 
+    #[test]
+    fn test_reader_notifies_writer_below_watermark() {
+        let (mut reader, mut writer) = Pipe::with_watermark(8, 4).into_split();
+
+        let mut write_task = spawn(writer.write_all(b"0123456789"));
+        assert_pending!(write_task.poll());
+
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(!write_task.is_woken());
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(!write_task.is_woken());
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(write_task.is_woken());
+
+        assert_ready!(write_task.poll()).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_watermark() {
+        let _ = Pipe::with_watermark(8, 0);
+    }
+
+    #[test]
+    fn test_seek_reports_position() {
+        let (mut reader, mut writer) = Pipe::new(16).into_split();
+        assert_ready!(spawn(writer.write_all(b"Hello, world!")).poll()).unwrap();
+
+        let mut buf = [0u8; 5];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(assert_ready!(spawn(reader.stream_position()).poll()).unwrap(), 5);
+        assert_eq!(assert_ready!(spawn(writer.stream_position()).poll()).unwrap(), 13);
+
+        let err = assert_ready!(spawn(reader.seek(io::SeekFrom::Start(0))).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let err = assert_ready!(spawn(writer.seek(io::SeekFrom::End(0))).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+
+        let mut pipe = Pipe::new(8);
+        assert_ready!(spawn(pipe.write_all(b"abc")).poll()).unwrap();
+        pipe.clear();
+        assert_eq!(assert_ready!(spawn(pipe.stream_position()).poll()).unwrap(), 3);
+    }
+
     #[test]
     fn test_writer_notifies_reader() {
         let (mut reader, mut writer) = Pipe::new(1024).into_split();
@@ -565,4 +1173,470 @@ mod tests {
         drop(read_task);
         assert_eq!(&buf[..], data);
     }
+
+    #[test]
+    fn test_buf_read_lines() {
+        let (reader, mut writer) = Pipe::new(16).into_split();
+        let mut lines = reader.lines();
+
+        let mut read_task = spawn(lines.next_line());
+        assert_pending!(read_task.poll());
+
+        assert_ready!(spawn(writer.write_all(b"Hello,\nwo")).poll()).unwrap();
+        assert!(read_task.is_woken());
+        let line = assert_ready!(read_task.poll()).unwrap();
+        assert_eq!(line.as_deref(), Some("Hello,"));
+        drop(read_task);
+
+        let mut read_task = spawn(lines.next_line());
+        assert_pending!(read_task.poll());
+
+        assert_ready!(spawn(writer.write_all(b"rld!\n")).poll()).unwrap();
+        assert!(read_task.is_woken());
+        let line = assert_ready!(read_task.poll()).unwrap();
+        assert_eq!(line.as_deref(), Some("world!"));
+        drop(read_task);
+
+        drop(writer);
+        let line = assert_ready!(spawn(lines.next_line()).poll()).unwrap();
+        assert_eq!(line, None);
+    }
+
+    #[test]
+    fn test_buf_read_consume_notifies_writer() {
+        let (mut reader, mut writer) = Pipe::new(4).into_split();
+
+        let mut write_task = spawn(writer.write_all(b"123456"));
+        assert_pending!(write_task.poll());
+
+        let buf = assert_ready!(spawn(reader.fill_buf()).poll()).unwrap();
+        assert_eq!(buf, b"1234");
+        reader.consume(3);
+        assert!(write_task.is_woken());
+        assert_ready!(write_task.poll()).unwrap();
+        drop(write_task);
+
+        let mut buf = Vec::new();
+        drop(writer);
+        assert_ready!(spawn(reader.read_to_end(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf[..], b"456");
+    }
+
+    #[test]
+    fn test_buf_read_slice_is_unaffected_by_writes() {
+        let (mut reader, mut writer) = Pipe::new(8).into_split();
+        assert_ready!(spawn(writer.write_all(b"1234")).poll()).unwrap();
+
+        let buf = assert_ready!(spawn(reader.fill_buf()).poll()).unwrap();
+        assert_ready!(spawn(writer.write_all(b"5678")).poll()).unwrap();
+        assert_eq!(buf, b"1234");
+        reader.consume(2);
+
+        let buf = assert_ready!(spawn(reader.fill_buf()).poll()).unwrap();
+        assert_eq!(buf, b"34");
+        reader.consume(2);
+
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"56");
+        let buf = assert_ready!(spawn(reader.fill_buf()).poll()).unwrap();
+        assert_eq!(buf, b"78");
+    }
+
+    #[test]
+    fn test_buffered_and_remaining() {
+        let (mut reader, mut writer) = Pipe::new(8).into_split();
+        assert_eq!(reader.buffered(), 0);
+        assert_eq!(writer.remaining(), 8);
+
+        assert_ready!(spawn(writer.write_all(b"12345")).poll()).unwrap();
+        assert_eq!(reader.buffered(), 5);
+        assert_eq!(writer.remaining(), 3);
+
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(reader.buffered(), 3);
+        assert_eq!(writer.remaining(), 5);
+    }
+
+    #[test]
+    fn test_clear_and_reset() {
+        let mut pipe = Pipe::new(4);
+
+        let mut write_task = spawn(pipe.write_all(b"123456"));
+        assert_pending!(write_task.poll());
+        drop(write_task);
+
+        pipe.clear();
+        assert!(pipe.buffer.is_empty());
+        assert_eq!(pipe.buffer.capacity(), 4);
+
+        assert_ready!(spawn(pipe.write_all(b"12")).poll()).unwrap();
+        assert_ready!(spawn(pipe.shutdown()).poll()).unwrap();
+        let err = assert_ready!(spawn(pipe.write_all(b"3")).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        pipe.reset();
+        assert_ready!(spawn(pipe.write_all(b"34")).poll()).unwrap();
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(pipe.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"34");
+    }
+
+    #[test]
+    fn test_duplex_shutdown_read() {
+        let (mut stream1, mut stream2) = duplex_pipe(1024);
+
+        stream1.shutdown_read();
+        let err = assert_ready!(spawn(stream2.write_all(b"Hello")).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        assert_ready!(spawn(stream1.write_all(b"world!")).poll()).unwrap();
+        let mut buf = [0u8; 6];
+        assert_ready!(spawn(stream2.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"world!");
+    }
+
+    #[test]
+    fn test_flush_drained() {
+        let (mut reader, mut writer) = Pipe::new(16).into_split();
+        assert_ready!(spawn(writer.flush_drained()).poll()).unwrap();
+
+        assert_ready!(spawn(writer.write_all(b"1234")).poll()).unwrap();
+        let mut flush_task = spawn(writer.flush_drained());
+        assert_pending!(flush_task.poll());
+
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(flush_task.is_woken());
+        assert_pending!(flush_task.poll());
+
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(flush_task.is_woken());
+        assert_ready!(flush_task.poll()).unwrap();
+        drop(flush_task);
+
+        assert_ready!(spawn(writer.write_all(b"5")).poll()).unwrap();
+        let mut flush_task = spawn(writer.flush_drained());
+        assert_pending!(flush_task.poll());
+        drop(reader);
+        assert!(flush_task.is_woken());
+        let err = assert_ready!(flush_task.poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_flush_drained_after_shutdown() {
+        let (mut reader, mut writer) = Pipe::new(16).into_split();
+        assert_ready!(spawn(writer.write_all(b"1234")).poll()).unwrap();
+        assert_ready!(spawn(writer.shutdown()).poll()).unwrap();
+
+        let mut flush_task = spawn(writer.flush_drained());
+        assert_pending!(flush_task.poll());
+
+        let mut buf = Vec::new();
+        assert_ready!(spawn(reader.read_to_end(&mut buf)).poll()).unwrap();
+        assert_eq!(buf, b"1234");
+        assert!(flush_task.is_woken());
+        assert_ready!(flush_task.poll()).unwrap();
+    }
+
+    #[test]
+    fn test_multiple_writers() {
+        let (mut reader, mut writer1) = Pipe::new(4).into_split();
+        let mut writer2 = writer1.clone();
+
+        assert_ready!(spawn(writer1.write_all(b"ab")).poll()).unwrap();
+        assert_ready!(spawn(writer2.write_all(b"cd")).poll()).unwrap();
+        let mut write1 = spawn(writer1.write_all(b"e"));
+        let mut write2 = spawn(writer2.write_all(b"f"));
+        assert_pending!(write1.poll());
+        assert_pending!(write2.poll());
+
+        let mut buf = [0u8; 4];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"abcd");
+        assert!(write1.is_woken());
+        assert!(write2.is_woken());
+        assert_ready!(write1.poll()).unwrap();
+        assert_ready!(write2.poll()).unwrap();
+        drop((write1, write2));
+
+        drop(writer1);
+        assert_eq!(assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap(), 2);
+        let mut read_task = spawn(reader.read(&mut buf));
+        assert_pending!(read_task.poll());
+
+        drop(writer2);
+        assert!(read_task.is_woken());
+        assert_eq!(assert_ready!(read_task.poll()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_shutdown_one_of_multiple_writers() {
+        let (mut reader, mut writer1) = Pipe::new(8).into_split();
+        let mut writer2 = writer1.clone();
+
+        assert_ready!(spawn(writer1.write_all(b"ab")).poll()).unwrap();
+        assert_ready!(spawn(writer1.shutdown()).poll()).unwrap();
+        let err = assert_ready!(spawn(writer1.write_all(b"x")).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        assert_ready!(spawn(writer2.write_all(b"cd")).poll()).unwrap();
+
+        let mut buf = [0u8; 8];
+        assert_eq!(assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap(), 4);
+        assert_eq!(&buf[..4], b"abcd");
+        let mut read_task = spawn(reader.read(&mut buf));
+        assert_pending!(read_task.poll());
+
+        drop(writer1);
+        assert!(!read_task.is_woken());
+        assert_ready!(spawn(writer2.shutdown()).poll()).unwrap();
+        assert!(read_task.is_woken());
+        assert_eq!(assert_ready!(read_task.poll()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_boxed_split() {
+        let (reader, writer) = Pipe::new(8).into_boxed_split();
+        let (duplex_reader, duplex_writer) = duplex_pipe(8).0.into_split();
+        let mut readers: Vec<BoxedReadEnd> = vec![reader, Box::pin(duplex_reader)];
+        let mut writers: Vec<BoxedWriteEnd> = vec![writer, Box::pin(duplex_writer)];
+
+        assert_ready!(spawn(writers[0].write_all(b"abc")).poll()).unwrap();
+        writers.clear();
+        let mut buf = String::new();
+        assert_ready!(spawn(readers[0].read_to_string(&mut buf)).poll()).unwrap();
+        assert_eq!(buf, "abc");
+    }
+
+    #[test]
+    fn test_abort_writer() {
+        let (mut reader, mut writer) = Pipe::new(8).into_split();
+        let mut read_task = spawn(reader.read_u8());
+        assert_pending!(read_task.poll());
+
+        assert_ready!(spawn(writer.write_all(b"12")).poll()).unwrap();
+        writer.abort(io::ErrorKind::ConnectionReset);
+        assert!(read_task.is_woken());
+        assert_eq!(assert_ready!(read_task.poll()).unwrap(), b'1');
+        drop(read_task);
+        assert_eq!(assert_ready!(spawn(reader.read_u8()).poll()).unwrap(), b'2');
+
+        let err = assert_ready!(spawn(reader.read_u8()).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+        let err = assert_ready!(spawn(reader.fill_buf()).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+
+        let mut chunks = spawn(reader.into_chunks());
+        let err = assert_ready!(chunks.poll_next()).unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let (mut reader, mut writer) = Pipe::new(8).into_split();
+
+        let err = assert_ready!(spawn(writer.write_atomic(b"123456789")).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        assert_ready!(spawn(writer.write_atomic(b"12345")).poll()).unwrap();
+        let mut write_task = spawn(writer.write_atomic(b"6789"));
+        assert_pending!(write_task.poll());
+        assert_eq!(reader.buffered(), 5);
+
+        let mut buf = [0u8; 2];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert!(write_task.is_woken());
+        assert_ready!(write_task.poll()).unwrap();
+        drop(write_task);
+
+        let mut buf = [0u8; 7];
+        assert_ready!(spawn(reader.read_exact(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf, b"3456789");
+
+        drop(reader);
+        let err = assert_ready!(spawn(writer.write_atomic(b"1")).poll()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_empty_write_completes_immediately() {
+        let (mut reader, mut writer) = Pipe::new(4).into_split();
+
+        let mut read_task = spawn(reader.read_u8());
+        assert_pending!(read_task.poll());
+
+        let written = assert_ready!(spawn(writer.write_vectored(&[io::IoSlice::new(&[])])).poll());
+        assert_eq!(written.unwrap(), 0);
+        let written = assert_ready!(spawn(writer.write(&[])).poll());
+        assert_eq!(written.unwrap(), 0);
+        assert!(!read_task.is_woken());
+
+        assert_ready!(spawn(writer.write_all(b"1234")).poll()).unwrap();
+        assert!(read_task.is_woken());
+        drop(read_task);
+
+        let written = assert_ready!(spawn(writer.write_vectored(&[io::IoSlice::new(&[])])).poll());
+        assert_eq!(written.unwrap(), 0);
+        let written = assert_ready!(spawn(writer.write(&[])).poll());
+        assert_eq!(written.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_into_chunks() {
+        let (reader, mut writer) = Pipe::new(4).into_split();
+        let mut chunks = spawn(reader.into_chunks());
+        assert_pending!(chunks.poll_next());
+
+        let mut write_task = spawn(writer.write_all(b"123456"));
+        assert_pending!(write_task.poll());
+        assert!(chunks.is_woken());
+        assert_eq!(assert_ready!(chunks.poll_next()).unwrap().unwrap(), b"1234");
+
+        assert!(write_task.is_woken());
+        assert_ready!(write_task.poll()).unwrap();
+        drop(write_task);
+        assert_eq!(assert_ready!(chunks.poll_next()).unwrap().unwrap(), b"56");
+        assert_pending!(chunks.poll_next());
+
+        drop(writer);
+        assert!(chunks.is_woken());
+        assert!(assert_ready!(chunks.poll_next()).is_none());
+    }
+
+    #[test]
+    fn test_length_delimited_messages_from_several_writers() {
+        use futures::SinkExt;
+
+        let (reader, writer1) = Pipe::new(16).into_split();
+        let mut sink1 = writer1.clone().into_length_delimited();
+        let mut sink2 = writer1.into_length_delimited();
+        let mut messages = spawn(reader.into_length_delimited());
+
+        assert_ready!(spawn(sink1.send(b"aaaaaaaa".to_vec())).poll()).unwrap();
+        let mut send2 = spawn(sink2.send(b"bbbbbbbb".to_vec()));
+        assert_pending!(send2.poll());
+        let mut send1 = spawn(sink1.send(b"cccccccc".to_vec()));
+        assert_pending!(send1.poll());
+
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"aaaaaaaa");
+        assert!(send1.is_woken());
+        assert!(send2.is_woken());
+        assert_ready!(send1.poll()).unwrap();
+        assert_pending!(send2.poll());
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"cccccccc");
+
+        assert!(send2.is_woken());
+        assert_ready!(send2.poll()).unwrap();
+        drop((send1, send2));
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"bbbbbbbb");
+
+        drop((sink1, sink2));
+        assert!(assert_ready!(messages.poll_next()).is_none());
+    }
+
+    #[test]
+    fn test_length_delimited_close_one_of_several_writers() {
+        use futures::SinkExt;
+
+        let (reader, writer) = Pipe::new(16).into_split();
+        let mut sink1 = writer.clone().into_length_delimited();
+        let mut sink2 = writer.into_length_delimited();
+        let mut messages = spawn(reader.into_length_delimited());
+
+        assert_ready!(spawn(sink1.send(b"a".to_vec())).poll()).unwrap();
+        assert_ready!(spawn(sink1.close()).poll()).unwrap();
+        assert_ready!(spawn(sink2.send(b"b".to_vec())).poll()).unwrap();
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"a");
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"b");
+        assert_pending!(messages.poll_next());
+
+        assert_ready!(spawn(sink2.close()).poll()).unwrap();
+        assert!(messages.is_woken());
+        assert!(assert_ready!(messages.poll_next()).is_none());
+    }
+
+    #[test]
+    fn test_length_delimited_messages() {
+        use futures::SinkExt;
+
+        let (reader, writer) = Pipe::new(6).into_split();
+        let mut sink = writer.into_length_delimited();
+        let mut messages = spawn(reader.into_length_delimited());
+        assert_pending!(messages.poll_next());
+
+        let mut send_task = spawn(sink.send(b"hello".to_vec()));
+        assert_pending!(send_task.poll());
+        assert!(messages.is_woken());
+        assert_pending!(messages.poll_next());
+        assert!(send_task.is_woken());
+        assert_ready!(send_task.poll()).unwrap();
+        drop(send_task);
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"hello");
+
+        assert_ready!(spawn(sink.send(vec![])).poll()).unwrap();
+        assert_eq!(assert_ready!(messages.poll_next()).unwrap().unwrap(), b"");
+
+        assert_ready!(spawn(sink.close()).poll()).unwrap();
+        assert!(assert_ready!(messages.poll_next()).is_none());
+    }
+
+    #[test]
+    fn test_length_delimited_truncated_message() {
+        let (reader, mut writer) = Pipe::new(16).into_split();
+        let mut messages = spawn(reader.into_length_delimited());
+
+        assert_ready!(spawn(writer.write_all(&[0, 0, 0, 4, b'a'])).poll()).unwrap();
+        assert_pending!(messages.poll_next());
+        drop(writer);
+
+        let err = assert_ready!(messages.poll_next()).unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(assert_ready!(messages.poll_next()).is_none());
+    }
+
+    #[test]
+    fn test_take_limits_read_bytes() {
+        let (reader, mut writer) = Pipe::new(32).into_split();
+        assert_ready!(spawn(writer.write_all(b"HeaderBody-and-more")).poll()).unwrap();
+
+        let mut header = [0u8; 6];
+        let mut limited = take(reader, 10);
+        assert_ready!(spawn(limited.read_exact(&mut header)).poll()).unwrap();
+        assert_eq!(&header, b"Header");
+        assert_eq!(limited.remaining(), 4);
+
+        let mut body = Vec::new();
+        assert_ready!(spawn(limited.read_to_end(&mut body)).poll()).unwrap();
+        assert_eq!(body, b"Body");
+        assert_eq!(limited.remaining(), 0);
+
+        let mut rest = Vec::new();
+        let mut reader = limited.into_inner();
+        assert_eq!(reader.buffered(), 9);
+        drop(writer);
+        assert_ready!(spawn(reader.read_to_end(&mut rest)).poll()).unwrap();
+        assert_eq!(rest, b"-and-more");
+    }
+
+    #[test]
+    fn test_take_waits_for_data() {
+        let (reader, mut writer) = Pipe::new(32).into_split();
+        let mut limited = take(reader, 4);
+
+        let mut buf = Vec::new();
+        let mut read = spawn(limited.read_to_end(&mut buf));
+        assert_pending!(read.poll());
+
+        assert_ready!(spawn(writer.write_all(b"ab")).poll()).unwrap();
+        assert!(read.is_woken());
+        assert_pending!(read.poll());
+
+        assert_ready!(spawn(writer.write_all(b"cdef")).poll()).unwrap();
+        assert!(read.is_woken());
+        assert_ready!(read.poll()).unwrap();
+        drop(read);
+        assert_eq!(buf, b"abcd");
+    }
 }
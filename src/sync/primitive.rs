@@ -0,0 +1,165 @@
+//! Building blocks for custom single-threaded synchronization primitives.
+
+use super::shared_state::SharedState;
+pub use super::shared_state::Source;
+use std::fmt;
+use std::ops::{ControlFlow, Deref};
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Shared handle to a user-defined [`Source`] that one task can wait on while others notify it.
+/// This is the machinery behind e.g. [`condvar`](super::condvar) and [`unbounded`](super::unbounded).
+///
+/// Only the waker of the most recent [`poll_wait()`](WaitQueue::poll_wait) call is stored, and only that task
+/// is woken on notification. Any other task waiting at the same time is not woken, so there should be at most one
+/// waiting task at a time, typically the owner of a dedicated "receiver" handle.
+/// ```
+/// use local_async_utils::sync::primitive::{Source, WaitQueue};
+/// use std::cell::Cell;
+/// use std::future::poll_fn;
+/// use std::ops::ControlFlow;
+///
+/// struct Tokens(Cell<usize>);
+///
+/// impl Source for Tokens {
+///     type Item = ();
+///
+///     fn try_yield_one(&self) -> ControlFlow<Option<()>> {
+///         match self.0.get() {
+///             0 => ControlFlow::Continue(()),
+///             n => {
+///                 self.0.set(n - 1);
+///                 ControlFlow::Break(Some(()))
+///             }
+///         }
+///     }
+/// }
+///
+/// # futures::executor::block_on(async {
+/// let mut consumer = WaitQueue::new(Tokens(Cell::new(0)));
+/// let producer = consumer.clone();
+///
+/// producer.0.set(1);
+/// producer.notify();
+/// assert_eq!(poll_fn(|cx| consumer.poll_wait(cx)).await, Some(()));
+/// # });
+/// ```
+pub struct WaitQueue<S>(Rc<SharedState<S>>);
+
+impl<S> WaitQueue<S> {
+    pub fn new(source: S) -> Self {
+        Self(SharedState::new(source))
+    }
+
+    /// Wake the waiting task, if any. Should be called after making an item available in the source.
+    pub fn notify(&self) {
+        self.0.notify();
+    }
+
+    /// Forget the waiting task without waking it, e.g. when the waiting handle is dropped.
+    pub fn clear_waiter(&self) {
+        self.0.receiver_dropped();
+    }
+}
+
+impl<S: Source> WaitQueue<S> {
+    /// Try to get an item from the source, or register the current task to be woken by [`notify()`](WaitQueue::notify).
+    pub fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        self.0.poll_wait(cx)
+    }
+
+    /// Try to get an item from the source without registering for a wakeup.
+    pub fn try_wait(&self) -> ControlFlow<Option<S::Item>> {
+        self.0.try_yield_one()
+    }
+}
+
+impl<S> Clone for WaitQueue<S> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<S> Deref for WaitQueue<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for WaitQueue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WaitQueue").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    struct Flag {
+        value: Cell<Option<bool>>,
+    }
+
+    impl Source for Flag {
+        type Item = ();
+
+        fn try_yield_one(&self) -> ControlFlow<Option<()>> {
+            match self.value.take() {
+                Some(true) => ControlFlow::Break(Some(())),
+                Some(false) => ControlFlow::Break(None),
+                None => ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_wait_queue_static_properties() {
+        assert_not_impl_any!(WaitQueue<usize>: Send, Sync);
+        assert_not_impl_any!(Arc<WaitQueue<usize>>: Send, Sync);
+    }
+
+    #[test]
+    fn test_notify_wakes_waiter() {
+        let mut waiter = WaitQueue::new(Flag {
+            value: Cell::new(None),
+        });
+        let notifier = waiter.clone();
+
+        let mut wait = spawn(std::future::poll_fn(|cx| waiter.poll_wait(cx)));
+        assert_pending!(wait.poll());
+
+        notifier.value.set(Some(true));
+        notifier.notify();
+        assert!(wait.is_woken());
+        assert_eq!(assert_ready!(wait.poll()), Some(()));
+        assert_pending!(wait.poll());
+
+        notifier.value.set(Some(false));
+        notifier.notify();
+        assert!(wait.is_woken());
+        assert_eq!(assert_ready!(wait.poll()), None);
+    }
+
+    #[test]
+    fn test_clear_waiter() {
+        let mut waiter = WaitQueue::new(Flag {
+            value: Cell::new(None),
+        });
+        let notifier = waiter.clone();
+
+        let mut wait = spawn(std::future::poll_fn(|cx| waiter.poll_wait(cx)));
+        assert_pending!(wait.poll());
+
+        notifier.clear_waiter();
+        notifier.notify();
+        assert!(!wait.is_woken());
+        assert_eq!(notifier.try_wait(), ControlFlow::Continue(()));
+    }
+}
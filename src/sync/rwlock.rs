@@ -0,0 +1,337 @@
+use crate::sealed;
+use crate::sync::waker_cell::Waiter;
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Asynchronous reader-writer lock for single-threaded use. Waiting tasks acquire the lock in FIFO order,
+/// so readers arriving after a waiting writer queue up behind it instead of starving it.
+pub struct RwLock<T> {
+    readers: Cell<usize>,
+    writer: Cell<bool>,
+    waiters: sealed::Queue<(Rc<Waiter>, Access)>,
+    value: UnsafeCell<T>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Shared,
+    Exclusive,
+}
+
+impl<T> RwLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            readers: Cell::new(0),
+            writer: Cell::new(false),
+            waiters: sealed::Queue::new(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        Lock {
+            lock: self,
+            access: Access::Shared,
+            waiter: None,
+        }
+        .await;
+        RwLockReadGuard(self)
+    }
+
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        Lock {
+            lock: self,
+            access: Access::Exclusive,
+            waiter: None,
+        }
+        .await;
+        RwLockWriteGuard(self)
+    }
+
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        self.try_acquire(Access::Shared).then(|| RwLockReadGuard(self))
+    }
+
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.try_acquire(Access::Exclusive).then(|| RwLockWriteGuard(self))
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    fn try_acquire(&self, access: Access) -> bool {
+        let available = match access {
+            Access::Shared => !self.writer.get(),
+            Access::Exclusive => !self.writer.get() && self.readers.get() == 0,
+        };
+        // don't overtake tasks that are already waiting
+        if available && self.waiters.is_empty() {
+            self.grant(access);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn grant(&self, access: Access) {
+        match access {
+            Access::Shared => self.readers.update(|n| n + 1),
+            Access::Exclusive => self.writer.set(true),
+        }
+    }
+
+    fn release(&self, access: Access) {
+        match access {
+            Access::Shared => self.readers.update(|n| n - 1),
+            Access::Exclusive => self.writer.set(false),
+        }
+        self.grant_waiters();
+    }
+
+    /// Hand the lock over to as many waiters from the front of the queue as possible.
+    fn grant_waiters(&self) {
+        while let Some(access) = self.waiters.front_map(|(_, access)| *access) {
+            let available = match access {
+                Access::Shared => !self.writer.get(),
+                Access::Exclusive => !self.writer.get() && self.readers.get() == 0,
+            };
+            if !available {
+                break;
+            }
+            let Some((waiter, _)) = self.waiters.pop() else {
+                break;
+            };
+            self.grant(access);
+            waiter.granted.set(true);
+            waiter.waker.take_and_wake();
+        }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLock")
+            .field("readers", &self.readers.get())
+            .field("writer", &self.writer.get())
+            .field("waiters", &self.waiters.len())
+            .finish_non_exhaustive()
+    }
+}
+
+struct Lock<'a, T> {
+    lock: &'a RwLock<T>,
+    access: Access,
+    waiter: Option<Rc<Waiter>>,
+}
+
+impl<T> Future for Lock<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match &this.waiter {
+            None if this.lock.try_acquire(this.access) => Poll::Ready(()),
+            None => {
+                let waiter = Rc::new(Waiter::default());
+                waiter.waker.update(cx);
+                this.lock.waiters.push((waiter.clone(), this.access));
+                this.waiter = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.granted.get() => {
+                this.waiter = None;
+                Poll::Ready(())
+            }
+            Some(waiter) => {
+                waiter.waker.update(cx);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Lock<'_, T> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            if waiter.granted.get() {
+                self.lock.release(self.access);
+            } else {
+                self.lock.waiters.remove_first(|(w, _)| Rc::ptr_eq(w, &waiter));
+                // tasks queued behind this one might be able to proceed now
+                self.lock.grant_waiters();
+            }
+        }
+    }
+}
+
+/// RAII guard providing shared access to the data protected by a [`RwLock`]. Releases the lock when dropped.
+pub struct RwLockReadGuard<'a, T>(&'a RwLock<T>);
+
+impl<T> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the guard guarantees that there are no writers
+        unsafe { &*self.0.value.get() }
+    }
+}
+
+impl<T> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.release(Access::Shared);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RwLockReadGuard").field(&**self).finish()
+    }
+}
+
+/// RAII guard providing exclusive access to the data protected by a [`RwLock`]. Releases the lock when dropped.
+pub struct RwLockWriteGuard<'a, T>(&'a RwLock<T>);
+
+impl<T> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the guard guarantees exclusive access
+        unsafe { &*self.0.value.get() }
+    }
+}
+
+impl<T> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the guard guarantees exclusive access
+        unsafe { &mut *self.0.value.get() }
+    }
+}
+
+impl<T> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.release(Access::Exclusive);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RwLockWriteGuard").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_rwlock_static_properties() {
+        assert_not_impl_any!(RwLock<usize>: Sync);
+        assert_not_impl_any!(RwLockReadGuard<'static, usize>: std::marker::Send, Sync);
+        assert_not_impl_any!(RwLockWriteGuard<'static, usize>: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_multiple_readers_or_one_writer() {
+        let lock = RwLock::new(1);
+
+        let read1 = assert_ready!(spawn(lock.read()).poll());
+        let read2 = lock.try_read().unwrap();
+        assert_eq!(*read1 + *read2, 2);
+        assert!(lock.try_write().is_none());
+
+        let mut write = spawn(lock.write());
+        assert_pending!(write.poll());
+        drop(read1);
+        assert!(!write.is_woken());
+        drop(read2);
+        assert!(write.is_woken());
+
+        let mut guard = assert_ready!(write.poll());
+        *guard += 1;
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+        drop(guard);
+        drop(write);
+
+        assert_eq!(*lock.try_read().unwrap(), 2);
+        assert_eq!(lock.into_inner(), 2);
+    }
+
+    #[test]
+    fn test_readers_queue_behind_waiting_writer() {
+        let lock = RwLock::new(Vec::new());
+        let read = lock.try_read().unwrap();
+
+        let mut write = spawn(lock.write());
+        assert_pending!(write.poll());
+        assert!(lock.try_read().is_none());
+        let mut read1 = spawn(lock.read());
+        let mut read2 = spawn(lock.read());
+        assert_pending!(read1.poll());
+        assert_pending!(read2.poll());
+
+        drop(read);
+        assert!(write.is_woken());
+        assert!(!read1.is_woken());
+        let mut guard = assert_ready!(write.poll());
+        guard.push(1);
+        drop(guard);
+
+        assert!(read1.is_woken());
+        assert!(read2.is_woken());
+        let guard1 = assert_ready!(read1.poll());
+        let guard2 = assert_ready!(read2.poll());
+        assert_eq!(*guard1, [1]);
+        assert_eq!(*guard2, [1]);
+    }
+
+    #[test]
+    fn test_cancelled_writer_lets_readers_through() {
+        let lock = RwLock::new(0);
+        let read = lock.try_read().unwrap();
+
+        let mut write = spawn(lock.write());
+        assert_pending!(write.poll());
+        let mut read1 = spawn(lock.read());
+        assert_pending!(read1.poll());
+
+        drop(write);
+        assert!(read1.is_woken());
+        let _guard = assert_ready!(read1.poll());
+        drop(read);
+    }
+
+    #[test]
+    fn test_granted_but_cancelled_writer_releases_lock() {
+        let lock = RwLock::new(0);
+        let guard = lock.try_write().unwrap();
+
+        let mut write = spawn(lock.write());
+        assert_pending!(write.poll());
+        let mut read = spawn(lock.read());
+        assert_pending!(read.poll());
+
+        drop(guard);
+        assert!(write.is_woken());
+        assert!(!read.is_woken());
+        drop(write);
+
+        assert!(read.is_woken());
+        let _guard = assert_ready!(read.poll());
+    }
+}
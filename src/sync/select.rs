@@ -0,0 +1,188 @@
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::task::Poll;
+
+/// Output of [`race2()`] indicating which of the futures completed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+/// Wait for the first of two futures to complete and drop the other one.
+/// The polling is biased: `a` is always polled before `b`, so if both are ready
+/// the output of `a` is returned. Put e.g. shutdown signals first to make them take priority.
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use local_async_utils::sync::select::{Either, race2};
+/// # futures::executor::block_on(async {
+/// let (_sender, mut receiver) = local_condvar::condvar();
+///
+/// let ret = race2(receiver.wait_for_one(), async { 42 }).await;
+/// assert!(matches!(ret, Either::Right(42)));
+/// # });
+/// ```
+pub async fn race2<A, B>(a: A, b: B) -> Either<A::Output, B::Output>
+where
+    A: Future,
+    B: Future,
+{
+    let mut a = pin!(a);
+    let mut b = pin!(b);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = a.as_mut().poll(cx) {
+            Poll::Ready(Either::Left(output))
+        } else {
+            b.as_mut().poll(cx).map(Either::Right)
+        }
+    })
+    .await
+}
+
+/// Wait for the first of several futures to complete and run the corresponding handler. Like [`race2()`],
+/// the polling is biased: the futures are polled in the listed order. All futures are dropped before the handler runs,
+/// so they can borrow e.g. channel receivers that the handlers use again. The patterns must be irrefutable,
+/// arms cannot be disabled by a failing match. Must be used within an async context.
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use futures::StreamExt;
+/// # futures::executor::block_on(async {
+/// let (_shutdown_sender, mut shutdown) = local_unbounded::channel::<()>();
+/// let (sender, mut receiver) = local_unbounded::channel::<i32>();
+/// sender.send(21).unwrap();
+///
+/// let ret = local_select! {
+///     _ = shutdown.next() => { 0 },
+///     item = receiver.next() => { item.unwrap() * 2 },
+/// };
+/// assert_eq!(ret, 42);
+/// # });
+/// ```
+/// Refutable patterns are rejected at compile time:
+/// ```compile_fail
+/// # use local_async_utils::prelude::*;
+/// # futures::executor::block_on(async {
+/// let ret = local_select! {
+///     Some(n) = async { Some(1) } => { n },
+/// };
+/// # });
+/// ```
+#[macro_export]
+macro_rules! local_select {
+    (@acc [$(($fut_var:ident, $out_var:ident, $pat:pat, $fut:expr, $body:block))+]) => {{
+        $( let mut $out_var = ::std::option::Option::None; )+
+        {
+            $( let mut $fut_var = ::std::pin::pin!($fut); )+
+            ::std::future::poll_fn(|cx| {
+                $(
+                    if let ::std::task::Poll::Ready(output) =
+                        ::std::future::Future::poll($fut_var.as_mut(), cx)
+                    {
+                        $out_var = ::std::option::Option::Some(output);
+                        return ::std::task::Poll::Ready(());
+                    }
+                )+
+                ::std::task::Poll::Pending
+            })
+            .await;
+        }
+        $(
+            if let ::std::option::Option::Some(output) = $out_var {
+                let $pat = output;
+                $body
+            } else
+        )+
+        { ::std::unreachable!() }
+    }};
+    (@acc [$($done:tt)*] $pat:pat = $fut:expr => $body:block, $($rest:tt)*) => {
+        $crate::local_select!(@acc [$($done)* (fut, out, $pat, $fut, $body)] $($rest)*)
+    };
+    (@acc [$($done:tt)*] $pat:pat = $fut:expr => $body:block $($rest:tt)*) => {
+        $crate::local_select!(@acc [$($done)* (fut, out, $pat, $fut, $body)] $($rest)*)
+    };
+    (@acc $($invalid:tt)*) => {
+        compile_error!("expected `pattern = future => { handler }` arms")
+    };
+    ($($arms:tt)+) => {
+        $crate::local_select!(@acc [] $($arms)+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::{pending, ready};
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_race2_is_biased() {
+        let ret = assert_ready!(spawn(race2(ready(1), ready(2))).poll());
+        assert_eq!(ret, Either::Left(1));
+
+        let ret = assert_ready!(spawn(race2(pending::<()>(), ready(2))).poll());
+        assert_eq!(ret, Either::Right(2));
+
+        assert_pending!(spawn(race2(pending::<()>(), pending::<()>())).poll());
+    }
+
+    #[test]
+    fn test_local_select_is_biased() {
+        let ret = assert_ready!(
+            spawn(async {
+                local_select! {
+                    a = ready(1) => { a * 10 },
+                    b = ready(2) => { b * 100 },
+                }
+            })
+            .poll()
+        );
+        assert_eq!(ret, 10);
+
+        let ret = assert_ready!(
+            spawn(async {
+                local_select! {
+                    () = pending::<()>() => { 0 }
+                    b = ready(2) => { b * 100 }
+                }
+            })
+            .poll()
+        );
+        assert_eq!(ret, 200);
+    }
+
+    #[test]
+    fn test_local_select_handlers_can_use_receivers() {
+        use futures::StreamExt;
+
+        let (sender1, mut receiver1) = crate::sync::unbounded::channel::<i32>();
+        let (sender2, mut receiver2) = crate::sync::unbounded::channel::<&str>();
+
+        let mut select = spawn(async {
+            local_select! {
+                n = receiver1.next() => { (n, receiver1.queue().len()) },
+                s = receiver2.next() => { (s.map(str::len).map(|l| l as i32), receiver2.queue().len()) },
+            }
+        });
+        assert_pending!(select.poll());
+
+        sender2.send("abc").unwrap();
+        sender2.send("de").unwrap();
+        assert!(select.is_woken());
+        assert_eq!(assert_ready!(select.poll()), (Some(3), 1));
+        drop(sender1);
+    }
+
+    #[test]
+    fn test_race2_wakes_on_either() {
+        let notify = crate::sync::notify::Notify::new();
+        let (sender, receiver) = crate::sync::oneshot::channel::<i32>();
+
+        let mut race = spawn(race2(notify.notified(), receiver));
+        assert_pending!(race.poll());
+
+        sender.send(5).unwrap();
+        assert!(race.is_woken());
+        assert_eq!(assert_ready!(race.poll()), Either::Right(Some(5)));
+    }
+}
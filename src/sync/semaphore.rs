@@ -1,11 +1,15 @@
 use super::shared_state::{SharedState, Source};
+use crate::sealed;
+use crate::sync::waker_cell::Waiter;
 use futures::FutureExt;
 use std::cell::Cell;
-use std::fmt;
 use std::future::{Future, poll_fn};
+use std::mem::ManuallyDrop;
 use std::ops::ControlFlow;
+use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll};
+use std::{fmt, ptr};
 
 struct MpscData {
     capacity: Cell<usize>,
@@ -54,6 +58,13 @@ impl Sender {
         self.0.capacity.set(current_capacity + 1);
         self.0.notify();
     }
+
+    pub fn signal_many(&self, n: usize) {
+        #[cfg(debug_assertions)]
+        debug_assert!(self.0.has_receiver.get());
+        self.0.capacity.update(|cap| cap + n);
+        self.0.notify();
+    }
 }
 
 impl Drop for Sender {
@@ -74,6 +85,10 @@ impl Receiver {
         poll_fn(|cx| self.0.poll_wait(cx)).map(|v| v.is_some())
     }
 
+    pub fn try_acquire_one(&mut self) -> bool {
+        matches!(self.0.try_yield_one(), ControlFlow::Break(Some(())))
+    }
+
     pub fn drain(&mut self) -> usize {
         self.0.capacity.replace(0)
     }
@@ -98,27 +113,113 @@ impl fmt::Debug for Receiver {
 
 // ------------------------------------------------------------------------------------------------
 
-struct SemData {
+struct SemState {
     capacity: Cell<usize>,
+    waiters: sealed::Queue<Rc<Waiter>>,
 }
 
-impl Source for SemData {
-    type Item = ();
-
-    fn try_yield_one(&self) -> ControlFlow<Option<Self::Item>> {
+impl SemState {
+    fn try_acquire(&self) -> bool {
         if self.capacity.get() != 0 {
             self.capacity.update(|c| c - 1);
-            ControlFlow::Break(Some(()))
+            true
         } else {
-            ControlFlow::Continue(())
+            false
+        }
+    }
+
+    fn release(&self) {
+        // hand the permit directly over to the next waiter, if any
+        if let Some(waiter) = self.waiters.pop() {
+            waiter.granted.set(true);
+            waiter.waker.take_and_wake();
+        } else {
+            self.capacity.update(|c| c + 1);
         }
     }
 }
 
-type SemStateRc = Rc<SharedState<SemData>>;
+type SemStateRc = Rc<SemState>;
+
+/// Place of a single task in the FIFO queue of semaphore waiters.
+#[derive(Default)]
+struct PendingAcquire(Option<Rc<Waiter>>);
+
+impl PendingAcquire {
+    fn poll(&mut self, state: &SemState, cx: &mut Context<'_>) -> Poll<()> {
+        match &self.0 {
+            None if state.try_acquire() => Poll::Ready(()),
+            None => {
+                let waiter = Rc::new(Waiter::default());
+                waiter.waker.update(cx);
+                state.waiters.push(waiter.clone());
+                self.0 = Some(waiter);
+                Poll::Pending
+            }
+            Some(waiter) if waiter.granted.get() => {
+                self.0 = None;
+                Poll::Ready(())
+            }
+            Some(waiter) => {
+                waiter.waker.update(cx);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn cancel(&mut self, state: &SemState) {
+        if let Some(waiter) = self.0.take() {
+            if waiter.granted.get() {
+                state.release();
+            } else {
+                state.waiters.remove_first(|w| Rc::ptr_eq(w, &waiter));
+            }
+        }
+    }
+}
+
+/// Future returned by [`Semaphore::acquire_permit()`]. Gives up its place in the queue when dropped.
+#[must_use = "futures do nothing unless polled"]
+pub struct Acquire<'a> {
+    state: &'a SemStateRc,
+    pending: PendingAcquire,
+}
+
+impl Future for Acquire<'_> {
+    type Output = Permit;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.pending.poll(this.state, cx).map(|()| Permit(this.state.clone()))
+    }
+}
+
+impl fmt::Debug for Acquire<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Acquire")
+            .field("queued", &self.pending.0.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        self.pending.cancel(self.state);
+    }
+}
 
 pub struct Permit(SemStateRc);
 
+impl Permit {
+    /// Consume the permit without returning it to the semaphore, permanently reducing its capacity.
+    /// The capacity can be restored using [`Semaphore::add_permits()`].
+    pub fn forget(self) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards
+        drop(unsafe { ptr::read(&this.0) });
+    }
+}
+
 impl fmt::Debug for Permit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("Permit").finish()
@@ -127,51 +228,131 @@ impl fmt::Debug for Permit {
 
 impl Drop for Permit {
     fn drop(&mut self) {
-        self.0.capacity.update(|c| c + 1);
-        self.0.notify();
+        self.0.release();
+    }
+}
+
+/// Several permits acquired at once, see [`Semaphore::try_acquire_many()`]. Releases all of them when dropped.
+pub struct ManyPermit {
+    state: SemStateRc,
+    count: usize,
+}
+
+impl ManyPermit {
+    /// Number of permits held by this object.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Consume the permits without returning them to the semaphore, see [`Permit::forget()`].
+    pub fn forget(mut self) {
+        self.count = 0;
+    }
+}
+
+impl fmt::Debug for ManyPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManyPermit").field("count", &self.count).finish_non_exhaustive()
+    }
+}
+
+impl Drop for ManyPermit {
+    fn drop(&mut self) {
+        for _ in 0..self.count {
+            self.state.release();
+        }
     }
 }
 
-pub struct Semaphore(SemStateRc);
+/// Counting semaphore. Tasks waiting for permits acquire them in FIFO order.
+pub struct Semaphore {
+    state: SemStateRc,
+    pending: PendingAcquire,
+}
 
 impl Semaphore {
+    /// Create a semaphore with `capacity` permits. A zero-capacity semaphore blocks all acquirers
+    /// until permits are added using [`Semaphore::add_permits()`].
     pub fn new(capacity: usize) -> Self {
-        assert!(capacity > 0, "zero capacity semaphore is not allowed");
-        Self(SharedState::new(SemData {
-            capacity: Cell::new(capacity),
-        }))
+        Self {
+            state: Rc::new(SemState {
+                capacity: Cell::new(capacity),
+                waiters: sealed::Queue::new(),
+            }),
+            pending: Default::default(),
+        }
     }
 
-    pub async fn acquire_permit(&mut self) -> Permit {
-        poll_fn(|cx| self.0.poll_wait(cx)).await;
-        Permit(self.0.clone())
+    /// Wait for a permit. Can be awaited by several tasks at once, they will be served in FIFO order.
+    pub fn acquire_permit(&self) -> Acquire<'_> {
+        Acquire {
+            state: &self.state,
+            pending: Default::default(),
+        }
     }
 
     pub fn try_acquire_permit(&self) -> Option<Permit> {
-        match self.0.try_yield_one() {
-            ControlFlow::Break(Some(())) => Some(Permit(self.0.clone())),
-            _ => None,
+        if self.state.try_acquire() {
+            Some(Permit(self.state.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Like [`try_acquire_permit()`](Self::try_acquire_permit), but also returns the number of permits
+    /// that remain available afterwards.
+    pub fn try_acquire_permit_with_count(&self) -> (Option<Permit>, usize) {
+        let permit = self.try_acquire_permit();
+        (permit, self.state.capacity.get())
+    }
+
+    /// Acquire `n` permits at once if they are all available, otherwise leave the semaphore untouched.
+    pub fn try_acquire_many(&self, n: usize) -> Option<ManyPermit> {
+        if self.state.capacity.get() >= n {
+            self.state.capacity.update(|c| c - n);
+            Some(ManyPermit {
+                state: self.state.clone(),
+                count: n,
+            })
+        } else {
+            None
         }
     }
 
     pub fn poll_acquire_permit(&mut self, cx: &mut Context<'_>) -> Poll<Permit> {
-        match self.0.poll_wait(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Some(())) => Poll::Ready(Permit(self.0.clone())),
-            Poll::Ready(None) => unreachable!(),
+        self.pending.poll(&self.state, cx).map(|()| Permit(self.state.clone()))
+    }
+
+    /// Increase the capacity of the semaphore by `n` permits, waking up to `n` waiting tasks.
+    pub fn add_permits(&self, n: usize) {
+        for _ in 0..n {
+            self.state.release();
         }
     }
+
+    /// Permanently remove up to `n` currently available permits, see also [`Permit::forget()`].
+    /// Returns the number of permits actually removed.
+    pub fn shrink(&self, n: usize) -> usize {
+        let removed = self.state.capacity.get().min(n);
+        self.state.capacity.update(|c| c - removed);
+        removed
+    }
+
+    /// Number of permits that can be acquired without waiting.
+    pub fn available_permits(&self) -> usize {
+        self.state.capacity.get()
+    }
 }
 
 impl fmt::Debug for Semaphore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("Semaphore").field(&self.0.capacity).finish()
+        f.debug_tuple("Semaphore").field(&self.state.capacity).finish()
     }
 }
 
 impl Drop for Semaphore {
     fn drop(&mut self) {
-        self.0.receiver_dropped();
+        self.pending.cancel(&self.state);
     }
 }
 
@@ -246,9 +427,38 @@ mod tests {
         assert!(ret);
     }
 
+    #[test]
+    fn test_mpsc_semaphore_signal_many() {
+        let (notifier, mut waiter) = mpsc_semaphore(0);
+
+        let mut wait_fut = spawn(waiter.acquire_one());
+        assert_pending!(wait_fut.poll());
+
+        notifier.signal_many(3);
+        assert!(wait_fut.is_woken());
+        assert!(assert_ready!(wait_fut.poll()));
+        drop(wait_fut);
+
+        assert!(assert_ready!(spawn(waiter.acquire_one()).poll()));
+        assert!(assert_ready!(spawn(waiter.acquire_one()).poll()));
+        assert_pending!(spawn(waiter.acquire_one()).poll());
+    }
+
+    #[test]
+    fn test_mpsc_semaphore_try_acquire_one() {
+        let (notifier, mut waiter) = mpsc_semaphore(1);
+
+        assert!(waiter.try_acquire_one());
+        assert!(!waiter.try_acquire_one());
+
+        notifier.signal_one();
+        drop(notifier);
+        assert!(!waiter.try_acquire_one());
+    }
+
     #[test]
     fn test_semaphore() {
-        let mut sem = Semaphore::new(2);
+        let sem = Semaphore::new(2);
 
         // when
         let permit1 = assert_ready!(spawn(sem.acquire_permit()).poll());
@@ -282,4 +492,189 @@ mod tests {
 
         drop(sem);
     }
+
+    #[test]
+    fn test_semaphore_waiters_acquire_in_fifo_order() {
+        let sem = Semaphore::new(1);
+        let permit = sem.try_acquire_permit().unwrap();
+
+        let mut acquire1 = spawn(sem.acquire_permit());
+        let mut acquire2 = spawn(sem.acquire_permit());
+        let mut acquire3 = spawn(sem.acquire_permit());
+        assert_pending!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+        assert_pending!(acquire3.poll());
+
+        drop(permit);
+        assert!(acquire1.is_woken());
+        assert!(!acquire2.is_woken());
+        assert!(!acquire3.is_woken());
+        assert!(sem.try_acquire_permit().is_none());
+        assert_pending!(acquire3.poll());
+        assert_pending!(acquire2.poll());
+        let permit = assert_ready!(acquire1.poll());
+
+        drop(permit);
+        assert!(acquire2.is_woken());
+        assert!(!acquire3.is_woken());
+        let permit = assert_ready!(acquire2.poll());
+
+        drop(permit);
+        assert!(acquire3.is_woken());
+        let permit = assert_ready!(acquire3.poll());
+
+        drop(permit);
+        assert!(sem.try_acquire_permit().is_some());
+    }
+
+    #[test]
+    fn test_semaphore_cancelled_waiter_passes_permit_on() {
+        let sem = Semaphore::new(1);
+        let permit = sem.try_acquire_permit().unwrap();
+
+        let mut acquire1 = spawn(sem.acquire_permit());
+        let mut acquire2 = spawn(sem.acquire_permit());
+        let mut acquire3 = spawn(sem.acquire_permit());
+        assert_pending!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+        assert_pending!(acquire3.poll());
+
+        drop(acquire2);
+        drop(permit);
+        assert!(acquire1.is_woken());
+
+        drop(acquire1);
+        assert!(acquire3.is_woken());
+        let _permit = assert_ready!(acquire3.poll());
+    }
+
+    #[test]
+    fn test_semaphore_forget_and_add_permits() {
+        let sem = Semaphore::new(2);
+
+        sem.try_acquire_permit().unwrap().forget();
+        assert_eq!(sem.available_permits(), 1);
+        let permit = sem.try_acquire_permit().unwrap();
+        assert!(sem.try_acquire_permit().is_none());
+        drop(permit);
+        assert_eq!(sem.available_permits(), 1);
+
+        assert_eq!(sem.shrink(5), 1);
+        assert_eq!(sem.available_permits(), 0);
+
+        let mut acquire = spawn(sem.acquire_permit());
+        assert_pending!(acquire.poll());
+
+        sem.add_permits(2);
+        assert!(acquire.is_woken());
+        let _permit = assert_ready!(acquire.poll());
+        assert_eq!(sem.available_permits(), 1);
+    }
+
+    #[test]
+    fn test_semaphore_acquire_through_shared_reference() {
+        let sem = Rc::new(Semaphore::new(1));
+        let permit = sem.try_acquire_permit().unwrap();
+
+        let mut acquire1 = spawn({
+            let sem = sem.clone();
+            async move { sem.acquire_permit().await }
+        });
+        let mut acquire2 = spawn({
+            let sem = sem.clone();
+            async move { sem.acquire_permit().await }
+        });
+        assert_pending!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+
+        drop(permit);
+        assert!(acquire1.is_woken());
+        assert!(!acquire2.is_woken());
+        let permit = assert_ready!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+
+        drop(permit);
+        assert!(acquire2.is_woken());
+        let _permit = assert_ready!(acquire2.poll());
+        assert_eq!(sem.available_permits(), 0);
+    }
+
+    #[test]
+    fn test_semaphore_starting_with_zero_permits() {
+        let sem = Semaphore::new(0);
+        assert_eq!(sem.available_permits(), 0);
+        assert!(sem.try_acquire_permit().is_none());
+
+        let mut acquire1 = spawn(sem.acquire_permit());
+        let mut acquire2 = spawn(sem.acquire_permit());
+        assert_pending!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+
+        sem.add_permits(1);
+        assert!(acquire1.is_woken());
+        assert!(!acquire2.is_woken());
+        let _permit = assert_ready!(acquire1.poll());
+        assert_pending!(acquire2.poll());
+    }
+
+    #[test]
+    fn test_semaphore_try_acquire_permit_with_count() {
+        let sem = Semaphore::new(2);
+
+        let (permit1, remaining) = sem.try_acquire_permit_with_count();
+        assert!(permit1.is_some());
+        assert_eq!(remaining, 1);
+        let (permit2, remaining) = sem.try_acquire_permit_with_count();
+        assert!(permit2.is_some());
+        assert_eq!(remaining, 0);
+        let (permit3, remaining) = sem.try_acquire_permit_with_count();
+        assert!(permit3.is_none());
+        assert_eq!(remaining, 0);
+
+        drop(permit1);
+        assert_eq!(sem.try_acquire_permit_with_count().1, 0);
+    }
+
+    #[test]
+    fn test_semaphore_acquire_future_is_nameable() {
+        struct Task<'a> {
+            acquire: Acquire<'a>,
+        }
+
+        let sem = Semaphore::new(1);
+        let permit = sem.try_acquire_permit().unwrap();
+        let mut task = Task {
+            acquire: sem.acquire_permit(),
+        };
+        let mut acquire = spawn(&mut task.acquire);
+        assert_pending!(acquire.poll());
+
+        drop(permit);
+        assert!(acquire.is_woken());
+        let _permit = assert_ready!(acquire.poll());
+    }
+
+    #[test]
+    fn test_semaphore_try_acquire_many() {
+        let sem = Semaphore::new(5);
+
+        let permits = sem.try_acquire_many(3).unwrap();
+        assert_eq!(permits.count(), 3);
+        assert_eq!(sem.available_permits(), 2);
+        assert!(sem.try_acquire_many(3).is_none());
+        assert_eq!(sem.available_permits(), 2);
+
+        let _permit = sem.try_acquire_permit().unwrap();
+        let _permit = sem.try_acquire_permit().unwrap();
+        let mut acquire = spawn(sem.acquire_permit());
+        assert_pending!(acquire.poll());
+
+        drop(permits);
+        assert!(acquire.is_woken());
+        let _permit = assert_ready!(acquire.poll());
+        assert_eq!(sem.available_permits(), 2);
+
+        sem.try_acquire_many(2).unwrap().forget();
+        assert_eq!(sem.available_permits(), 0);
+    }
 }
@@ -3,8 +3,15 @@ use std::ops::{ControlFlow, Deref};
 use std::rc::Rc;
 use std::task::{Context, Poll};
 
-pub(super) trait Source {
+/// State that a single task can asynchronously wait on, see [`WaitQueue`](super::primitive::WaitQueue).
+pub trait Source {
     type Item;
+
+    /// Attempt to produce the next item.
+    /// # Returns
+    /// - `ControlFlow::Break(Some(item))` if an item is available.
+    /// - `ControlFlow::Break(None)` if no more items will ever be available, e.g. the producer is gone.
+    /// - `ControlFlow::Continue(())` if the waiter should be suspended until notified.
     fn try_yield_one(&self) -> ControlFlow<Option<Self::Item>>;
 }
 
@@ -13,7 +20,7 @@ pub(super) struct SharedState<T> {
     inner: T,
 }
 
-impl<T: Source> SharedState<T> {
+impl<T> SharedState<T> {
     pub(super) fn new(inner: T) -> Rc<Self> {
         Rc::new(Self {
             waker: Default::default(),
@@ -29,7 +36,9 @@ impl<T: Source> SharedState<T> {
         // remove waker so that we don't unnecessarily wake anyone when Sender is dropped
         self.waker.reset();
     }
+}
 
+impl<T: Source> SharedState<T> {
     // This should NEVER be called concurrently from different futures/tasks,
     // because we store only 1 waker
     pub(super) fn poll_wait(self: &mut Rc<Self>, cx: &mut Context<'_>) -> Poll<Option<T::Item>> {
@@ -1,17 +1,22 @@
 use super::shared_state::{SharedState, Source};
 use crate::sealed;
-use crate::sync::error::SendError;
+use crate::sync::error::{Closed, SendError, TryRecvError, TrySendError};
+use crate::sync::waker_cell::MultiWaker;
 use std::cell::Cell;
 use std::fmt;
+use std::future::poll_fn;
 use std::ops::ControlFlow;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll};
+#[cfg(feature = "tokio")]
+use std::{future::Future, mem, time::Duration};
 
 struct Data<T> {
     queue: sealed::Queue<T>,
     sender_count: Cell<usize>,
     has_receiver: Cell<bool>,
+    closed_waker: MultiWaker,
 }
 
 impl<T> Source for Data<T> {
@@ -36,10 +41,17 @@ pub struct Receiver<T>(StateRc<T>);
 
 /// Unbounded MPSC channel
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    channel_with_capacity(0)
+}
+
+/// Unbounded MPSC channel with space for at least `capacity` items preallocated.
+/// The capacity is not a limit, the queue will grow past it as needed.
+pub fn channel_with_capacity<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
     let state = SharedState::new(Data {
-        queue: Default::default(),
+        queue: sealed::Queue::with_capacity(capacity),
         sender_count: Cell::new(1),
         has_receiver: Cell::new(true),
+        closed_waker: Default::default(),
     });
     (Sender(state.clone()), Receiver(state))
 }
@@ -49,6 +61,7 @@ impl<T> Sender<T> {
         !self.0.has_receiver.get()
     }
 
+    #[must_use = "the item may not have been sent"]
     pub fn send(&self, item: T) -> Result<(), SendError<T>> {
         if self.is_closed() {
             Err(SendError::Closed(item))
@@ -59,9 +72,69 @@ impl<T> Sender<T> {
         }
     }
 
+    /// Send `item` only if fewer than `len_threshold` items are queued, giving a soft capacity limit
+    /// to an otherwise unbounded channel. The item is handed back on failure.
+    #[must_use = "the item may not have been sent"]
+    pub fn try_send(&self, len_threshold: usize, item: T) -> Result<(), TrySendError<T>> {
+        if self.is_closed() {
+            Err(TrySendError::Closed(item))
+        } else if self.0.queue.len() >= len_threshold {
+            Err(TrySendError::Full(item))
+        } else {
+            self.0.queue.push(item);
+            self.0.notify();
+            Ok(())
+        }
+    }
+
     pub fn queue(&self) -> &sealed::Queue<T> {
         &self.0.queue
     }
+
+    pub fn has_receiver(&self) -> bool {
+        self.0.has_receiver.get()
+    }
+
+    /// Wait until the receiver has been dropped.
+    pub async fn closed(&self) {
+        poll_fn(|cx| self.poll_closed(cx)).await
+    }
+
+    /// Polls to see if the receiver has been dropped.
+    /// # Returns
+    /// - `Poll::Ready(())` if the receiver has been dropped.
+    /// - `Poll::Pending` if the receiver is still alive.
+    pub fn poll_closed(&self, cx: &mut Context) -> Poll<()> {
+        if self.is_closed() {
+            Poll::Ready(())
+        } else {
+            self.0.closed_waker.update(cx);
+            Poll::Pending
+        }
+    }
+
+    /// Check whether `self` and `other` send to the same receiver.
+    pub fn same_channel(&self, other: &Sender<T>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Polls to see if the channel is ready to send a message. Never returns `Poll::Pending`, but mirrors
+    /// [`bounded::Sender::poll_ready()`](crate::sync::bounded::Sender::poll_ready) so that the two can be used
+    /// interchangeably.
+    /// # Returns
+    /// - `Poll::Ready(true)` if the message can be sent.
+    /// - `Poll::Ready(false)` if the receiver has been dropped.
+    pub fn poll_ready(&self, _cx: &mut Context) -> Poll<bool> {
+        Poll::Ready(!self.is_closed())
+    }
+
+    /// Remove all queued items for which `pred` returns `true`. Returns `true` if anything was removed.
+    pub fn remove_if<F>(&self, pred: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0.queue.remove_if(pred)
+    }
 }
 
 impl<T> Drop for Sender<T> {
@@ -97,6 +170,106 @@ impl<T> Receiver<T> {
     pub fn queue(&self) -> &sealed::Queue<T> {
         &self.0.queue
     }
+
+    /// Number of senders that are still alive. The channel is closed when this reaches zero.
+    pub fn sender_count(&self) -> usize {
+        self.0.sender_count.get()
+    }
+
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        match self.0.try_yield_one() {
+            ControlFlow::Break(Some(item)) => Ok(item),
+            ControlFlow::Break(None) => Err(TryRecvError::Disconnected),
+            ControlFlow::Continue(()) => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Remove and yield the queued items one by one. Doesn't wait for new items: the iterator ends as soon as
+    /// the channel is empty, regardless of whether the senders are alive.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.0.queue.pop())
+    }
+
+    /// Wait for the next item.
+    /// # Returns
+    /// - `Ok(item)` if an item has been received.
+    /// - `Err(Closed)` if all senders have been dropped and the channel is empty.
+    pub async fn recv_or_closed(&mut self) -> Result<T, Closed> {
+        poll_fn(|cx| self.0.poll_wait(cx)).await.ok_or(Closed)
+    }
+
+    /// Block the current thread until an item is received or all senders are dropped.
+    ///
+    /// Since the channel is not thread-safe, nothing can be sent while the thread is blocked.
+    /// This is therefore only a "poll until ready" helper for synchronous code (e.g. FFI callbacks)
+    /// that runs outside of the async runtime, and it will block forever if the channel is empty and open.
+    /// Must not be called from within an async context.
+    pub fn blocking_recv(&mut self) -> Option<T> {
+        futures::executor::block_on(futures::StreamExt::next(self))
+    }
+
+    /// Convert into a plain [`Stream`](futures::Stream). The receiver is always [`Unpin`], so the returned stream
+    /// can be fed into combinators that require `Unpin` without any pinning.
+    pub fn into_stream(self) -> impl futures::Stream<Item = T> + Unpin {
+        self
+    }
+
+    /// Convert into a stream of batches of up to `max` items. A partial batch is yielded once `duration` has passed
+    /// since its first item was received, or when the channel is closed.
+    ///
+    /// # Panics
+    /// If `max` is zero.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub fn chunks_timeout(
+        self,
+        max: usize,
+        duration: Duration,
+    ) -> impl futures::Stream<Item = Vec<T>> + Unpin {
+        assert!(max > 0, "chunk size must be non-zero");
+        ChunksTimeout {
+            receiver: self,
+            max,
+            duration,
+            batch: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// Convert into a stream that yields the received items transformed by `f`.
+    /// ```
+    /// # use local_async_utils::prelude::*;
+    /// # use futures::StreamExt;
+    /// # futures::executor::block_on(async {
+    /// let (sender, receiver) = local_unbounded::channel();
+    /// let mut lengths = receiver.map(|s: &str| s.len());
+    /// sender.send("hello").unwrap();
+    /// assert_eq!(lengths.next().await, Some(5));
+    /// # });
+    /// ```
+    pub fn map<U, F>(self, f: F) -> futures::stream::Map<Self, F>
+    where
+        F: FnMut(T) -> U,
+    {
+        futures::StreamExt::map(self, f)
+    }
+
+    /// Convert into a stream that yields only the received items for which `pred` returns `true`.
+    /// Unlike [`StreamExt::filter()`](futures::StreamExt::filter), `pred` returns a plain `bool` rather than a future.
+    pub fn filter_sync<F>(self, mut pred: F) -> impl futures::Stream<Item = T> + Unpin
+    where
+        F: FnMut(&T) -> bool,
+    {
+        futures::StreamExt::filter(self, move |item| std::future::ready(pred(item)))
+    }
+
+    /// Inspect the next item without removing it from the channel.
+    pub fn peek_map<R, F>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.0.queue.front_map(f)
+    }
 }
 
 impl<T> futures::Stream for Receiver<T> {
@@ -105,12 +278,131 @@ impl<T> futures::Stream for Receiver<T> {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         self.get_mut().0.poll_wait(cx)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.queue.len();
+        if self.is_closed() {
+            (len, Some(len))
+        } else {
+            (len, None)
+        }
+    }
+}
+
+impl<T> futures::stream::FusedStream for Receiver<T> {
+    fn is_terminated(&self) -> bool {
+        self.is_closed() && self.0.queue.is_empty()
+    }
 }
 
+#[cfg(feature = "tokio")]
+struct ChunksTimeout<T> {
+    receiver: Receiver<T>,
+    max: usize,
+    duration: Duration,
+    batch: Vec<T>,
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+// `Vec<T>` is only `Unpin` if `T` is, but nothing inside `ChunksTimeout` is ever accessed through a pinned reference.
+#[cfg(feature = "tokio")]
+impl<T> Unpin for ChunksTimeout<T> {}
+
+#[cfg(feature = "tokio")]
+impl<T> ChunksTimeout<T> {
+    fn take_batch(&mut self) -> Vec<T> {
+        self.deadline = None;
+        mem::take(&mut self.batch)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> futures::Stream for ChunksTimeout<T> {
+    type Item = Vec<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.receiver.0.poll_wait(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.batch.is_empty() {
+                        this.batch.reserve(this.max);
+                        this.deadline = Some(Box::pin(tokio::time::sleep(this.duration)));
+                    }
+                    this.batch.push(item);
+                    if this.batch.len() >= this.max {
+                        return Poll::Ready(Some(this.take_batch()));
+                    }
+                }
+                Poll::Ready(None) if this.batch.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => return Poll::Ready(Some(this.take_batch())),
+                Poll::Pending => break,
+            }
+        }
+        if let Some(deadline) = &mut this.deadline
+            && deadline.as_mut().poll(cx).is_ready()
+        {
+            return Poll::Ready(Some(this.take_batch()));
+        }
+        Poll::Pending
+    }
+}
+
+/// Adapt a channel of byte chunks into an `AsyncRead`. Chunks are read in order regardless of
+/// their sizes, and EOF is reported once the channel is closed and all chunks have been read.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub fn channel_reader(receiver: Receiver<Vec<u8>>) -> impl tokio::io::AsyncRead + Unpin {
+    ChannelReader {
+        receiver,
+        chunk: Vec::new(),
+        offset: 0,
+    }
+}
+
+#[cfg(feature = "tokio")]
+struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for ChannelReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        while this.offset == this.chunk.len() {
+            match this.receiver.0.poll_wait(cx) {
+                Poll::Ready(Some(chunk)) => {
+                    this.chunk = chunk;
+                    this.offset = 0;
+                }
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let len = buf.remaining().min(this.chunk.len() - this.offset);
+        buf.put_slice(&this.chunk[this.offset..this.offset + len]);
+        this.offset += len;
+        Poll::Ready(Ok(()))
+    }
+}
+
+// Only holds an `Rc`, so it is `Unpin` regardless of `T`.
+impl<T> Unpin for Receiver<T> {}
+
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         self.0.receiver_dropped();
         self.0.has_receiver.set(false);
+        self.0.closed_waker.wake_all();
     }
 }
 
@@ -136,6 +428,7 @@ mod tests {
         assert_not_impl_any!(Arc<Sender<usize>>: std::marker::Send, Sync);
         assert_not_impl_any!(Arc<Receiver<usize>>: std::marker::Send, Sync);
         assert_not_impl_any!(Sender<usize>: std::marker::Send, Sync);
+        assert_impl_all!(Receiver<std::marker::PhantomPinned>: Unpin);
         assert_not_impl_any!(Receiver<usize>: std::marker::Send, Sync);
     }
 
@@ -173,6 +466,246 @@ mod tests {
         assert_eq!(None, assert_ready!(receiver.poll_next()));
     }
 
+    #[test]
+    fn test_receiver_is_terminated() {
+        use futures::stream::FusedStream;
+
+        let (sender, receiver) = channel::<i32>();
+        let mut receiver = spawn(receiver);
+        assert!(!receiver.is_terminated());
+
+        sender.send(42).unwrap();
+        drop(sender);
+        assert!(!receiver.is_terminated());
+
+        assert_eq!(Some(42), assert_ready!(receiver.poll_next()));
+        assert!(receiver.is_terminated());
+    }
+
+    #[test]
+    fn test_receiver_size_hint() {
+        use futures::Stream;
+
+        let (sender, receiver) = channel::<i32>();
+        assert_eq!(receiver.size_hint(), (0, None));
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(receiver.size_hint(), (2, None));
+
+        drop(sender);
+        assert_eq!(receiver.size_hint(), (2, Some(2)));
+    }
+
+    #[test]
+    fn test_sender_remove_if() {
+        let (sender, receiver) = channel::<i32>();
+        for i in 0..5 {
+            sender.send(i).unwrap();
+        }
+        assert!(sender.remove_if(|i| i % 2 == 1));
+        assert!(!sender.remove_if(|i| *i > 10));
+        drop(sender);
+
+        let mut receiver = spawn(receiver);
+        assert_eq!(Some(0), assert_ready!(receiver.poll_next()));
+        assert_eq!(Some(2), assert_ready!(receiver.poll_next()));
+        assert_eq!(Some(4), assert_ready!(receiver.poll_next()));
+        assert_eq!(None, assert_ready!(receiver.poll_next()));
+    }
+
+    #[test]
+    fn test_receiver_peek_map() {
+        let (sender, receiver) = channel::<i32>();
+        assert_eq!(receiver.peek_map(|i| *i), None);
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(receiver.peek_map(|i| *i), Some(1));
+
+        let mut receiver = spawn(receiver);
+        assert_eq!(Some(1), assert_ready!(receiver.poll_next()));
+        assert_eq!(receiver.peek_map(|i| *i), Some(2));
+    }
+
+    #[test]
+    fn test_into_stream_is_unpin() {
+        use futures::StreamExt;
+
+        let (sender, receiver) = channel::<std::marker::PhantomPinned>();
+        let mut stream = receiver.into_stream();
+        let mut next = spawn(stream.next());
+        assert_pending!(next.poll());
+
+        sender.send(std::marker::PhantomPinned).unwrap();
+        assert!(next.is_woken());
+        assert!(assert_ready!(next.poll()).is_some());
+    }
+
+    #[test]
+    fn test_drain() {
+        let (sender, mut receiver) = channel::<i32>();
+        assert_eq!(receiver.drain().count(), 0);
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(receiver.drain().collect::<Vec<_>>(), [1, 2]);
+        assert!(receiver.queue().is_empty());
+        assert!(!receiver.is_closed());
+    }
+
+    #[test]
+    fn test_recv_or_closed() {
+        let (sender, mut receiver) = channel::<i32>();
+
+        let mut recv = spawn(receiver.recv_or_closed());
+        assert_pending!(recv.poll());
+        sender.send(1).unwrap();
+        assert!(recv.is_woken());
+        assert_eq!(assert_ready!(recv.poll()), Ok(1));
+        drop(recv);
+
+        sender.send(2).unwrap();
+        drop(sender);
+        assert_eq!(assert_ready!(spawn(receiver.recv_or_closed()).poll()), Ok(2));
+        assert_eq!(assert_ready!(spawn(receiver.recv_or_closed()).poll()), Err(Closed));
+    }
+
+    #[test]
+    fn test_try_recv() {
+        let (sender, mut receiver) = channel::<i32>();
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+
+        sender.send(1).unwrap();
+        drop(sender);
+        assert_eq!(receiver.try_recv(), Ok(1));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_blocking_recv() {
+        let (sender, mut receiver) = channel::<i32>();
+        sender.send(1).unwrap();
+        assert_eq!(receiver.blocking_recv(), Some(1));
+
+        drop(sender);
+        assert_eq!(receiver.blocking_recv(), None);
+    }
+
+    #[test]
+    fn test_sender_poll_ready() {
+        let (sender, receiver) = channel::<i32>();
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(true));
+
+        drop(receiver);
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(false));
+    }
+
+    #[test]
+    fn test_sender_try_send_with_threshold() {
+        let (sender, mut receiver) = channel::<String>();
+        sender.try_send(2, "a".to_owned()).unwrap();
+        sender.try_send(2, "b".to_owned()).unwrap();
+
+        let err = sender.try_send(2, "c".to_owned()).unwrap_err();
+        assert!(err.is_full());
+        assert_eq!(err.into_inner(), "c");
+
+        assert_eq!(receiver.try_recv().unwrap(), "a");
+        sender.try_send(2, "c".to_owned()).unwrap();
+
+        drop(receiver);
+        assert!(sender.try_send(10, "d".to_owned()).unwrap_err().is_closed());
+    }
+
+    #[test]
+    fn test_receiver_map_and_filter() {
+        use futures::StreamExt;
+
+        let (sender, receiver) = channel::<i32>();
+        let mut stream = spawn(receiver.filter_sync(|n| n % 2 == 0).map(|n| n * 10));
+        assert_pending!(stream.poll_next());
+
+        for i in 1..=4 {
+            sender.send(i).unwrap();
+        }
+        assert!(stream.is_woken());
+        assert_eq!(assert_ready!(stream.poll_next()), Some(20));
+        assert_eq!(assert_ready!(stream.poll_next()), Some(40));
+        drop(sender);
+        assert_eq!(assert_ready!(stream.poll_next()), None);
+    }
+
+    #[test]
+    fn test_receiver_map_and_stream_ext_filter() {
+        use futures::StreamExt;
+
+        let (sender, receiver) = channel::<i32>();
+        let mut evens = spawn(receiver.filter(|n| std::future::ready(n % 2 == 0)));
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(assert_ready!(evens.poll_next()), Some(2));
+        assert_pending!(evens.poll_next());
+
+        let (sender, receiver) = channel::<i32>();
+        let doubled: futures::stream::Map<Receiver<i32>, fn(i32) -> i32> = receiver.map(|n| n * 2);
+        let mut doubled = spawn(doubled);
+        sender.send(1).unwrap();
+        assert_eq!(assert_ready!(doubled.poll_next()), Some(2));
+    }
+
+    #[test]
+    fn test_channel_with_capacity() {
+        let (sender, mut receiver) = channel_with_capacity::<i32>(16);
+        for i in 0..32 {
+            sender.send(i).unwrap();
+        }
+        assert_eq!(receiver.queue().len(), 32);
+        assert_eq!(receiver.drain().collect::<Vec<_>>(), (0..32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_senders_notified_when_closed() {
+        let (sender1, receiver) = channel::<i32>();
+        let sender2 = sender1.clone();
+
+        let mut closed1 = spawn(sender1.closed());
+        let mut closed2 = spawn(sender2.closed());
+        assert_pending!(closed1.poll());
+        assert_pending!(closed2.poll());
+
+        drop(receiver);
+        assert!(closed1.is_woken());
+        assert!(closed2.is_woken());
+        assert_ready!(closed1.poll());
+        assert_ready!(closed2.poll());
+        assert_ready!(spawn(sender1.closed()).poll());
+    }
+
+    #[test]
+    fn test_sender_same_channel() {
+        let (sender1, _receiver1) = channel::<i32>();
+        let (sender2, _receiver2) = channel::<i32>();
+        assert!(sender1.same_channel(&sender1.clone()));
+        assert!(!sender1.same_channel(&sender2));
+    }
+
+    #[test]
+    fn test_sender_and_receiver_count() {
+        let (sender, receiver) = channel::<i32>();
+        assert_eq!(receiver.sender_count(), 1);
+
+        let sender2 = sender.clone();
+        assert_eq!(receiver.sender_count(), 2);
+        drop(sender);
+        assert_eq!(receiver.sender_count(), 1);
+
+        assert!(sender2.has_receiver());
+        drop(receiver);
+        assert!(!sender2.has_receiver());
+    }
+
     #[test]
     fn test_sender_is_closed() {
         let (sender, receiver) = channel::<i32>();
@@ -196,4 +729,90 @@ mod tests {
         drop(sender2);
         assert!(receiver.is_closed());
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_channel_reader_chunks_smaller_than_buffer() {
+        use tokio::io::AsyncReadExt;
+
+        let (sender, receiver) = channel();
+        let mut reader = channel_reader(receiver);
+        let mut buf = [0u8; 8];
+
+        assert_pending!(spawn(reader.read(&mut buf)).poll());
+        sender.send(b"abc".to_vec()).unwrap();
+        sender.send(vec![]).unwrap();
+        sender.send(b"de".to_vec()).unwrap();
+
+        let n = assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf[..n], b"abc");
+        let n = assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf[..n], b"de");
+
+        drop(sender);
+        let n = assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap();
+        assert_eq!(n, 0);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_channel_reader_chunks_larger_than_buffer() {
+        use tokio::io::AsyncReadExt;
+
+        let (sender, receiver) = channel();
+        let mut reader = channel_reader(receiver);
+        sender.send(b"hello".to_vec()).unwrap();
+        sender.send(b" world".to_vec()).unwrap();
+        drop(sender);
+
+        let mut buf = [0u8; 2];
+        let n = assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf[..n], b"he");
+        let n = assert_ready!(spawn(reader.read(&mut buf)).poll()).unwrap();
+        assert_eq!(&buf[..n], b"ll");
+
+        let mut rest = String::new();
+        assert_ready!(spawn(reader.read_to_string(&mut rest)).poll()).unwrap();
+        assert_eq!(rest, "o world");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn test_chunks_timeout() {
+        let (sender, receiver) = channel::<i32>();
+        let mut chunks = spawn(receiver.chunks_timeout(3, crate::millisec!(5)));
+        assert_pending!(chunks.poll_next());
+
+        for i in 0..4 {
+            sender.send(i).unwrap();
+        }
+        assert!(chunks.is_woken());
+        assert_eq!(assert_ready!(chunks.poll_next()), Some(vec![0, 1, 2]));
+        assert_pending!(chunks.poll_next());
+
+        tokio::time::advance(crate::millisec!(4)).await;
+        assert_pending!(chunks.poll_next());
+        sender.send(4).unwrap();
+        assert_pending!(chunks.poll_next());
+
+        tokio::time::advance(crate::millisec!(1)).await;
+        assert!(chunks.is_woken());
+        assert_eq!(assert_ready!(chunks.poll_next()), Some(vec![3, 4]));
+        assert_pending!(chunks.poll_next());
+
+        sender.send(5).unwrap();
+        drop(sender);
+        assert_eq!(assert_ready!(chunks.poll_next()), Some(vec![5]));
+        assert_eq!(assert_ready!(chunks.poll_next()), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn test_chunks_timeout_is_unpin() {
+        let (sender, receiver) = channel::<std::marker::PhantomPinned>();
+        let mut chunks = receiver.chunks_timeout(1, crate::millisec!(5));
+        sender.send(std::marker::PhantomPinned).unwrap();
+        let batch = futures::StreamExt::next(&mut chunks).await;
+        assert_eq!(batch.map(|batch| batch.len()), Some(1));
+    }
 }
@@ -1,4 +1,6 @@
-use std::cell::UnsafeCell;
+use crate::sealed;
+use std::cell::{Cell, UnsafeCell};
+use std::rc::Rc;
 use std::task::{Context, Waker};
 
 #[derive(Default)]
@@ -22,3 +24,55 @@ impl WakerCell {
         *waker = None;
     }
 }
+
+/// Like [`WakerCell`] but for multiple tasks waiting for the same event.
+#[derive(Default)]
+pub(super) struct MultiWaker(sealed::Queue<Waker>);
+
+impl MultiWaker {
+    pub(super) fn update(&self, cx: &mut Context) {
+        self.0.remove_first(|w| w.will_wake(cx.waker()));
+        self.0.push(cx.waker().clone());
+    }
+
+    pub(super) fn wake_all(&self) {
+        self.0.take_all().into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Wakers of all receivers of a multi-consumer channel, each receiver waiting in its own task.
+/// Unlike [`MultiWaker`], a receiver stays registered until it is dropped, see [`unregister()`](Self::unregister).
+#[derive(Default)]
+pub(super) struct ReceiverWakers(sealed::Queue<Rc<WakerCell>>);
+
+impl ReceiverWakers {
+    pub(super) fn register(&self) -> Rc<WakerCell> {
+        let waker = Rc::new(WakerCell::default());
+        self.0.push(waker.clone());
+        waker
+    }
+
+    pub(super) fn unregister(&self, waker: &Rc<WakerCell>) {
+        self.0.remove_first(|w| Rc::ptr_eq(w, waker));
+    }
+
+    pub(super) fn wake_all(&self) {
+        // iterate over a snapshot, as waking may drop receivers
+        for waker in self.0.iter_cloned() {
+            waker.take_and_wake();
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Slot of a task parked in a FIFO queue of waiters.
+#[derive(Default)]
+pub(super) struct Waiter {
+    pub(super) waker: WakerCell,
+    /// Whatever the task was waiting for has been handed over to it.
+    pub(super) granted: Cell<bool>,
+}
@@ -0,0 +1,253 @@
+use crate::sync::error::SendError;
+use crate::sync::waker_cell::{ReceiverWakers, WakerCell};
+use std::cell::{Cell, Ref, RefCell};
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+struct State<T> {
+    value: RefCell<T>,
+    version: Cell<usize>,
+    has_sender: Cell<bool>,
+    receiver_count: Cell<usize>,
+    rx_wakers: ReceiverWakers,
+}
+
+impl<T> State<T> {
+    fn register_receiver(self: &Rc<Self>, version: usize) -> Receiver<T> {
+        let waker = self.rx_wakers.register();
+        self.receiver_count.update(|count| count + 1);
+        Receiver {
+            state: self.clone(),
+            waker,
+            seen_version: version,
+        }
+    }
+
+    fn notify_all(&self) {
+        self.rx_wakers.wake_all();
+    }
+}
+
+/// Single-producer multi-consumer channel that only retains the latest sent value.
+/// Non-thread-safe equivalent of [`tokio::sync::watch`](https://docs.rs/tokio/latest/tokio/sync/watch/index.html).
+pub fn channel<T>(initial: T) -> (Sender<T>, Receiver<T>) {
+    let state = Rc::new(State {
+        value: RefCell::new(initial),
+        version: Cell::new(0),
+        has_sender: Cell::new(true),
+        receiver_count: Cell::new(0),
+        rx_wakers: Default::default(),
+    });
+    let receiver = state.register_receiver(0);
+    (Sender(state), receiver)
+}
+
+pub struct Sender<T>(Rc<State<T>>);
+
+impl<T> Sender<T> {
+    /// Overwrite the current value and notify all receivers.
+    /// Fails if there are no receivers.
+    ///
+    /// # Panics
+    /// If a view returned by `borrow()` or `borrow_and_update()` of the sender or of any receiver is still alive,
+    /// e.g. because it is being held across a suspension point.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            Err(SendError::Closed(value))
+        } else {
+            *self.0.value.borrow_mut() = value;
+            self.0.version.update(|v| v.wrapping_add(1));
+            self.0.notify_all();
+            Ok(())
+        }
+    }
+
+    /// Get a view of the current value. Must not be held while sending, see [`send()`](Self::send).
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.value.borrow()
+    }
+
+    /// Create a new receiver that will be notified of all values sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.0.register_receiver(self.0.version.get())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.receiver_count.get() == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.0.has_sender.set(false);
+        self.0.notify_all();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("version", &self.0.version.get())
+            .field("receiver_count", &self.0.receiver_count.get())
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Receiver<T> {
+    state: Rc<State<T>>,
+    waker: Rc<WakerCell>,
+    seen_version: usize,
+}
+
+impl<T> Receiver<T> {
+    /// Get a view of the current value without marking it as seen.
+    /// Must not be held while the sender is sending, e.g. across suspension points, see [`Sender::send()`].
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.state.value.borrow()
+    }
+
+    /// Get a view of the current value and mark it as seen.
+    /// Must not be held while the sender is sending, see [`Sender::send()`].
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.seen_version = self.state.version.get();
+        self.state.value.borrow()
+    }
+
+    /// Check whether the current value hasn't been seen by this receiver yet.
+    pub fn has_changed(&self) -> bool {
+        self.seen_version != self.state.version.get()
+    }
+
+    /// Wait until a value that hasn't been seen by this receiver is sent, and mark it as seen.
+    /// # Returns
+    /// - `true` if the value has changed.
+    /// - `false` if the sender has been dropped.
+    pub fn changed(&mut self) -> impl Future<Output = bool> + '_ {
+        poll_fn(|cx| self.poll_changed(cx))
+    }
+
+    fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<bool> {
+        if self.has_changed() {
+            self.seen_version = self.state.version.get();
+            Poll::Ready(true)
+        } else if !self.state.has_sender.get() {
+            Poll::Ready(false)
+        } else {
+            self.waker.update(cx);
+            Poll::Pending
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        !self.state.has_sender.get()
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.state.register_receiver(self.seen_version)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.state.receiver_count.update(|count| count - 1);
+        self.state.rx_wakers.unregister(&self.waker);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("seen_version", &self.seen_version)
+            .field("has_sender", &self.state.has_sender.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_not_impl_any;
+    use std::sync::Arc;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[test]
+    fn test_channel_static_properties() {
+        assert_not_impl_any!(Arc<Sender<usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Arc<Receiver<usize>>: std::marker::Send, Sync);
+        assert_not_impl_any!(Sender<usize>: std::marker::Send, Sync, Clone);
+        assert_not_impl_any!(Receiver<usize>: std::marker::Send, Sync);
+    }
+
+    #[test]
+    fn test_receivers_observe_latest_value() {
+        let (sender, mut receiver1) = channel(0);
+        let mut receiver2 = receiver1.clone();
+        assert_eq!(*receiver1.borrow(), 0);
+        assert!(!receiver1.has_changed());
+
+        let mut changed1 = spawn(receiver1.changed());
+        let mut changed2 = spawn(receiver2.changed());
+        assert_pending!(changed1.poll());
+        assert_pending!(changed2.poll());
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert!(changed1.is_woken());
+        assert!(changed2.is_woken());
+        assert!(assert_ready!(changed1.poll()));
+        assert!(assert_ready!(changed2.poll()));
+        drop((changed1, changed2));
+
+        assert_eq!(*receiver1.borrow(), 2);
+        assert_eq!(*receiver2.borrow(), 2);
+        assert_pending!(spawn(receiver1.changed()).poll());
+    }
+
+    #[test]
+    fn test_subscribe_and_borrow_and_update() {
+        let (sender, receiver) = channel("a");
+        sender.send("b").unwrap();
+        assert!(receiver.has_changed());
+
+        let mut subscriber = sender.subscribe();
+        assert!(!subscriber.has_changed());
+
+        sender.send("c").unwrap();
+        assert!(subscriber.has_changed());
+        assert_eq!(*subscriber.borrow_and_update(), "c");
+        assert!(!subscriber.has_changed());
+    }
+
+    #[test]
+    fn test_sender_dropped() {
+        let (sender, mut receiver) = channel(0);
+
+        let mut changed = spawn(receiver.changed());
+        assert_pending!(changed.poll());
+
+        drop(sender);
+        assert!(changed.is_woken());
+        assert!(!assert_ready!(changed.poll()));
+        drop(changed);
+        assert!(receiver.is_closed());
+    }
+
+    #[test]
+    fn test_receivers_dropped() {
+        let (sender, receiver) = channel(0);
+        let receiver2 = receiver.clone();
+        drop(receiver);
+        assert!(!sender.is_closed());
+
+        drop(receiver2);
+        assert!(sender.is_closed());
+        assert_eq!(sender.0.rx_wakers.len(), 0);
+        assert_eq!(sender.send(1), Err(SendError::Closed(1)));
+        assert_eq!(*sender.borrow(), 0);
+    }
+}
@@ -1,3 +1,20 @@
+//! Duration shortcut macros and timer utilities. `timeout` and `interval` require the `tokio` feature.
+
+#[cfg(feature = "tokio")]
+use crate::sync::select::{Either, race2};
+#[cfg(feature = "tokio")]
+use futures::Stream;
+use std::{error::Error, fmt, io};
+#[cfg(feature = "tokio")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Duration,
+};
+#[cfg(feature = "tokio")]
+use tokio::time::{Instant, Sleep};
+
 /// Shortcut for [`std::time::Duration::from_secs`].
 /// ```
 /// # use local_async_utils::prelude::*;
@@ -30,3 +47,265 @@ macro_rules! millisec {
 macro_rules! min {
     ($arg:expr) => {{ std::time::Duration::from_secs($arg * 60) }};
 }
+
+/// Error returned by [`timeout()`] when the deadline has passed before the future completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(e: Elapsed) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, e)
+    }
+}
+
+/// Run `fut` to completion unless `duration` elapses first, in which case `fut` is dropped.
+/// Unlike [`tokio::time::timeout`] this imposes no `Send` bounds and never polls the timer
+/// before the future, so a future that is ready straight away always wins.
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use local_async_utils::time::{Elapsed, timeout};
+/// # tokio_test::block_on(async {
+/// let ret = timeout(sec!(1), async { 42 }).await;
+/// assert_eq!(ret, Ok(42));
+///
+/// let ret = timeout(millisec!(1), std::future::pending::<()>()).await;
+/// assert_eq!(ret, Err(Elapsed));
+/// # });
+/// ```
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub async fn timeout<F: Future>(duration: Duration, fut: F) -> Result<F::Output, Elapsed> {
+    match race2(fut, tokio::time::sleep(duration)).await {
+        Either::Left(output) => Ok(output),
+        Either::Right(()) => Err(Elapsed),
+    }
+}
+
+/// Defines what [`interval_with()`] does when a tick has been polled too late.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// Yield all missed ticks back-to-back until the schedule has caught up.
+    #[default]
+    Burst,
+    /// Schedule the next tick one period after the moment the late tick was yielded.
+    Delay,
+    /// Drop missed ticks and wait for the next multiple of the period on the original schedule.
+    Skip,
+}
+
+#[cfg(feature = "tokio")]
+struct Interval {
+    sleep: Pin<Box<Sleep>>,
+    period: Duration,
+    behavior: MissedTickBehavior,
+}
+
+#[cfg(feature = "tokio")]
+impl Interval {
+    fn next_deadline(&self, deadline: Instant, now: Instant) -> Instant {
+        match self.behavior {
+            MissedTickBehavior::Burst => deadline + self.period,
+            MissedTickBehavior::Delay => now + self.period,
+            MissedTickBehavior::Skip => {
+                let since_last = (now - deadline).as_nanos() % self.period.as_nanos();
+                now + self.period - Duration::from_nanos(since_last as u64)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        ready!(self.sleep.as_mut().poll(cx));
+        let next = self.next_deadline(self.sleep.deadline(), Instant::now());
+        self.sleep.as_mut().reset(next);
+        Poll::Ready(Some(()))
+    }
+}
+
+/// Infinite stream that yields every `period`, with the first tick completing immediately.
+/// Equivalent to [`interval_with()`] with [`MissedTickBehavior::Burst`].
+/// ```
+/// # use local_async_utils::prelude::*;
+/// # use futures::StreamExt;
+/// # tokio_test::block_on(async {
+/// let mut ticks = std::pin::pin!(local_async_utils::time::interval(millisec!(1)));
+/// ticks.next().await; // first tick is immediate
+/// ticks.next().await;
+/// # });
+/// ```
+///
+/// # Panics
+/// If `period` is zero.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub fn interval(period: Duration) -> impl Stream<Item = ()> + Unpin {
+    interval_with(period, MissedTickBehavior::Burst)
+}
+
+/// Same as [`interval()`] but with custom handling of missed ticks.
+///
+/// # Panics
+/// If `period` is zero.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub fn interval_with(
+    period: Duration,
+    behavior: MissedTickBehavior,
+) -> impl Stream<Item = ()> + Unpin {
+    assert!(!period.is_zero(), "interval period must be non-zero");
+    Interval {
+        sleep: Box::pin(tokio::time::sleep_until(Instant::now())),
+        period,
+        behavior,
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::sync::oneshot;
+    use futures::future::pending;
+    use tokio_test::task::spawn;
+    use tokio_test::{assert_pending, assert_ready};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_elapses() {
+        let mut fut = spawn(timeout(sec!(5), pending::<()>()));
+        assert_pending!(fut.poll());
+
+        tokio::time::advance(sec!(4)).await;
+        assert_pending!(fut.poll());
+
+        tokio::time::advance(sec!(1)).await;
+        assert!(fut.is_woken());
+        assert_eq!(assert_ready!(fut.poll()), Err(Elapsed));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_completes_before_deadline() {
+        let (sender, receiver) = oneshot::channel::<i32>();
+        let mut fut = spawn(timeout(sec!(5), receiver));
+        assert_pending!(fut.poll());
+
+        tokio::time::advance(sec!(2)).await;
+        sender.send(42).unwrap();
+        assert!(fut.is_woken());
+        assert_eq!(assert_ready!(fut.poll()), Ok(Some(42)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ready_future_wins_over_zero_timeout() {
+        let ret = timeout(Duration::ZERO, async { 1 }).await;
+        assert_eq!(ret, Ok(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_ticks_every_period() {
+        let mut ticks = spawn(interval(sec!(2)));
+        assert_ready!(ticks.poll_next());
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert!(ticks.is_woken());
+        assert_ready!(ticks.poll_next());
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(2)).await;
+        assert!(ticks.is_woken());
+        assert_ready!(ticks.poll_next());
+        assert_pending!(ticks.poll_next());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_burst_catches_up() {
+        let mut ticks = spawn(interval_with(sec!(2), MissedTickBehavior::Burst));
+        assert_ready!(ticks.poll_next());
+
+        tokio::time::advance(sec!(7)).await;
+        assert_ready!(ticks.poll_next()); // t=2
+        assert_ready!(ticks.poll_next()); // t=4
+        assert_ready!(ticks.poll_next()); // t=6
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert_ready!(ticks.poll_next()); // t=8
+        assert_pending!(ticks.poll_next());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_delay_reschedules_from_now() {
+        let mut ticks = spawn(interval_with(sec!(2), MissedTickBehavior::Delay));
+        assert_ready!(ticks.poll_next());
+
+        tokio::time::advance(sec!(7)).await;
+        assert_ready!(ticks.poll_next()); // t=7
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert_ready!(ticks.poll_next()); // t=9
+        assert_pending!(ticks.poll_next());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_skip_keeps_original_schedule() {
+        let mut ticks = spawn(interval_with(sec!(2), MissedTickBehavior::Skip));
+        assert_ready!(ticks.poll_next());
+
+        tokio::time::advance(sec!(7)).await;
+        assert_ready!(ticks.poll_next()); // t=7, missed ticks at 2, 4, 6
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(sec!(1)).await;
+        assert!(ticks.is_woken());
+        assert_ready!(ticks.poll_next()); // t=8
+        assert_pending!(ticks.poll_next());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_skip_after_long_stall() {
+        let mut ticks = spawn(interval_with(millisec!(1), MissedTickBehavior::Skip));
+        assert_ready!(ticks.poll_next());
+
+        // more missed ticks than fit in a u32
+        tokio::time::advance(min!(60 * 24 * 60)).await;
+        assert_ready!(ticks.poll_next());
+        assert_pending!(ticks.poll_next());
+
+        tokio::time::advance(millisec!(1)).await;
+        assert!(ticks.is_woken());
+        assert_ready!(ticks.poll_next());
+        assert_pending!(ticks.poll_next());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interval_rejects_zero_period() {
+        let _ = interval(Duration::ZERO);
+    }
+
+    #[test]
+    fn test_elapsed_into_io_error() {
+        let e = io::Error::from(Elapsed);
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+    }
+}